@@ -1,8 +1,10 @@
 use aws_sdk_sqs::types::Message as SdkMessage;
+use aws_sdk_sqs::types::MessageAttributeValue;
 use aws_sdk_sqs::types::builders::MessageBuilder as SdkMessageBuilder;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use tokio::time::Instant;
 
 use crate::httpx::{AppContext, HttpError};
 
@@ -25,6 +27,14 @@ impl Message {
         self.0.message_id()
     }
 
+    /// String value of a message attribute, such as the `x-request-id` set by the outbox.
+    pub fn message_attribute(&self, name: &str) -> Option<&str> {
+        self.0
+            .message_attributes()
+            .and_then(|attributes| attributes.get(name))
+            .and_then(|attribute| attribute.string_value())
+    }
+
     pub(crate) fn receipt_handle(&self) -> Option<&str> {
         self.0.receipt_handle()
     }
@@ -45,6 +55,15 @@ impl MessageBuilder {
         Self(self.0.receipt_handle(rh))
     }
 
+    pub fn message_attribute(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let attribute = MessageAttributeValue::builder()
+            .data_type("String")
+            .string_value(value)
+            .build()
+            .expect("data type is set");
+        Self(self.0.message_attributes(name, attribute))
+    }
+
     pub fn build(self) -> Message {
         Message::new(self.0.build())
     }
@@ -62,6 +81,7 @@ where
 {
     pub(crate) queue_url: String,
     pub(crate) handler: HandlerFn<S>,
+    pub(crate) last_successful_poll: Arc<Mutex<Instant>>,
 }
 
 impl<S> SqsQueueConsumer<S>
@@ -76,6 +96,13 @@ where
         Self {
             queue_url: queue_url.to_string(),
             handler: Arc::new(move |ctx, msg| Box::pin(handler(ctx, msg))),
+            last_successful_poll: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    pub(crate) fn record_successful_poll(&self) {
+        if let Ok(mut last_successful_poll) = self.last_successful_poll.lock() {
+            *last_successful_poll = Instant::now();
         }
     }
 }
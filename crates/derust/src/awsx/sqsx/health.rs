@@ -0,0 +1,103 @@
+use super::consumer::SqsQueueConsumer;
+use crate::httpx::{AppContext, HealthCheck, HealthIndicator};
+use serde_json::{json, Map, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Reports the time since the last successful poll of every SQS consumer,
+/// failing once any of them exceeds `max_poll_age`.
+pub(crate) struct SqsHealthIndicator {
+    polls: Vec<(String, Arc<Mutex<Instant>>)>,
+    max_poll_age: Duration,
+}
+
+impl SqsHealthIndicator {
+    pub(crate) fn new<S>(consumers: &[SqsQueueConsumer<S>], max_poll_age: Duration) -> Self
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        Self {
+            polls: consumers
+                .iter()
+                .map(|consumer| (consumer.queue_url.clone(), consumer.last_successful_poll.clone()))
+                .collect(),
+            max_poll_age,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> HealthIndicator<S> for SqsHealthIndicator
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn name(&self) -> &str {
+        "sqs"
+    }
+
+    fn critical(&self) -> bool {
+        false
+    }
+
+    async fn check(&self, _context: &AppContext<S>) -> HealthCheck {
+        let mut healthy = true;
+        let mut queues = Map::new();
+
+        for (queue_url, last_successful_poll) in &self.polls {
+            let age = last_successful_poll
+                .lock()
+                .map(|last_successful_poll| last_successful_poll.elapsed())
+                .unwrap_or(Duration::MAX);
+
+            healthy &= age <= self.max_poll_age;
+            queues.insert(
+                queue_url.clone(),
+                json!({ "last_successful_poll_age_in_secs": age.as_secs() }),
+            );
+        }
+
+        let details = json!({
+            "max_poll_age_in_secs": self.max_poll_age.as_secs(),
+            "queues": Value::Object(queues),
+        });
+
+        if healthy {
+            HealthCheck::ok().with_details(details)
+        } else {
+            HealthCheck::failure().with_details(details)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::httpx::{test_context, HealthStatus};
+
+    #[tokio::test]
+    async fn should_fail_once_a_consumer_exceeds_the_max_poll_age() {
+        let context = test_context(()).await;
+        let last_successful_poll = Arc::new(Mutex::new(Instant::now()));
+        let indicator = SqsHealthIndicator {
+            polls: vec![
+                ("orders".to_string(), Arc::new(Mutex::new(Instant::now()))),
+                ("payments".to_string(), last_successful_poll.clone()),
+            ],
+            max_poll_age: Duration::from_secs(120),
+        };
+
+        let check = HealthIndicator::<()>::check(&indicator, &context).await;
+        assert_eq!(check.status(), &HealthStatus::Ok);
+
+        *last_successful_poll.lock().unwrap() = Instant::now() - Duration::from_secs(121);
+
+        let check = HealthIndicator::<()>::check(&indicator, &context).await;
+        assert_eq!(check.status(), &HealthStatus::Failure);
+        assert_eq!(
+            serde_json::to_value(&check).unwrap()["details"]["queues"]["payments"]["last_successful_poll_age_in_secs"],
+            121
+        );
+        assert!(!HealthIndicator::<()>::critical(&indicator));
+    }
+}
@@ -1,5 +1,7 @@
 mod consumer;
+mod health;
 mod runner;
 
 pub use consumer::{Message, MessageBuilder, SqsQueueConsumer};
 pub use runner::run;
+pub(crate) use health::SqsHealthIndicator;
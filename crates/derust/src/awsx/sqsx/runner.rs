@@ -2,18 +2,16 @@ use aws_sdk_sqs::types::Message as SdkMessage;
 use std::time::Duration;
 use tracing::log::{log_enabled, Level};
 use tracing::{error, info};
-use wg::WaitGroup;
 
 use super::consumer::{Message, SqsQueueConsumer};
 use crate::awsx::{load_aws_config, sqs_client, SqsClient};
-use crate::httpx::AppContext;
-use crate::shutdown_signal;
+use crate::httpx::middlewares::request_id;
+use crate::httpx::{AppContext, REQUEST_ID_HEADER};
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 use crate::metricx::{start_stopwatch, MetricTags};
 
 pub async fn run<T>(
-    wg: WaitGroup,
     context: AppContext<T>,
     consumers: Vec<SqsQueueConsumer<T>>,
 ) where
@@ -34,8 +32,6 @@ pub async fn run<T>(
         let _ = handle.await;
     }
 
-    wg.done();
-
     info!("SQS consumers stopped");
 }
 
@@ -46,7 +42,7 @@ async fn poll_queue<T>(
 ) where
     T: Clone + Send + Sync + 'static,
 {
-    let mut shutdown = Box::into_pin(Box::new(shutdown_signal()));
+    let shutdown = context.shutdown().token();
 
     loop {
         let recv = sqs
@@ -55,12 +51,14 @@ async fn poll_queue<T>(
             .queue_url(&consumer.queue_url)
             .max_number_of_messages(10)
             .wait_time_seconds(20)
+            .message_attribute_names("All")
             .send();
 
         tokio::select! {
             result = recv => {
                 match result {
                     Ok(output) => {
+                        consumer.record_successful_poll();
                         for sdk_msg in output.messages.unwrap_or_default() {
                             process_message(&context, &sqs, &consumer, sdk_msg).await;
                         }
@@ -71,7 +69,7 @@ async fn poll_queue<T>(
                     }
                 }
             }
-            _ = &mut shutdown => {
+            _ = shutdown.cancelled() => {
                 info!("SQS consumer for queue {} stopped", consumer.queue_url);
                 break;
             }
@@ -88,14 +86,28 @@ async fn process_message<T>(
     T: Clone + Send + Sync + 'static,
 {
     let msg = Message::new(sdk_msg);
+    let request_id = request_id::accept_or_generate(msg.message_attribute(REQUEST_ID_HEADER));
+
+    // logs and outgoing calls of the handler carry the id of the request that produced the message
+    request_id::with_request_id(request_id, handle_message(context, sqs, consumer, msg)).await;
+}
+
+async fn handle_message<T>(
+    context: &AppContext<T>,
+    sqs: &SqsClient,
+    consumer: &SqsQueueConsumer<T>,
+    msg: Message,
+) where
+    T: Clone + Send + Sync + 'static,
+{
     let message_id = msg.message_id().unwrap_or("unknown").to_string();
     let body = msg.body().unwrap_or("").to_string();
     let receipt_handle = msg.receipt_handle().unwrap_or_default().to_string();
 
-    #[cfg(any(feature = "statsd", feature = "prometheus"))]
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
     let metric_tags = MetricTags::default().push("queue_url".to_string(), consumer.queue_url.clone());
 
-    #[cfg(any(feature = "statsd", feature = "prometheus"))]
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
     let stopwatch = start_stopwatch(context, "sqs_consumer_seconds", metric_tags);
 
     let result = (consumer.handler)(context.clone(), msg).await;
@@ -105,7 +117,7 @@ async fn process_message<T>(
             if context.env().is_local() || log_enabled!(Level::Debug) {
                 info!(
                     "SQS message consumed :: queue={} :: message_id={} :: body={}",
-                    consumer.queue_url, message_id, body
+                    consumer.queue_url, message_id, context.redaction().redact_text(&body)
                 );
             } else {
                 info!(
@@ -123,21 +135,23 @@ async fn process_message<T>(
                 .await;
         }
         Err(err) => {
+            let error_message = context.redaction().redact_text(&format!("{err:?}"));
+
             if context.env().is_local() || log_enabled!(Level::Debug) {
                 error!(
-                    "SQS message failed :: queue={} :: message_id={} :: body={} :: error={:?}",
-                    consumer.queue_url, message_id, body, err
+                    "SQS message failed :: queue={} :: message_id={} :: body={} :: error={}",
+                    consumer.queue_url, message_id, context.redaction().redact_text(&body), error_message
                 );
             } else {
                 error!(
-                    "SQS message failed :: queue={} :: message_id={} :: error={:?}",
-                    consumer.queue_url, message_id, err
+                    "SQS message failed :: queue={} :: message_id={} :: error={}",
+                    consumer.queue_url, message_id, error_message
                 );
             }
         }
     }
 
-    #[cfg(any(feature = "statsd", feature = "prometheus"))]
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
     {
         let success = if result.is_ok() { "true" } else { "false" };
         stopwatch.record(MetricTags::default().push("success".to_string(), success.to_string()));
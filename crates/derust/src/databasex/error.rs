@@ -0,0 +1,170 @@
+use crate::httpx::{HttpError, HttpTags};
+use axum::http::StatusCode;
+use std::fmt::{Display, Formatter};
+
+const SERIALIZATION_FAILURE_CODES: [&str; 2] = ["40001", "40P01"];
+const TIMEOUT_CODES: [&str; 2] = ["57014", "55P03"];
+
+/// Database failures, kept as the source of the [`HttpError`]s of the repositories
+/// and readable through [`HttpError::source_as`].
+#[derive(Debug)]
+pub enum DatabaseError {
+    /// `fetch_one`, `count` or `exists` returned no row.
+    NotFound,
+    UniqueViolation { constraint: Option<String> },
+    ForeignKeyViolation { constraint: Option<String> },
+    /// Serialization failure or deadlock, the transaction can be retried.
+    SerializationFailure,
+    /// Statement or lock timeout, or a timed out connection.
+    Timeout,
+    /// No connection became available in the pool in time.
+    PoolExhausted,
+    Other(sqlx::Error),
+}
+
+impl Display for DatabaseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DatabaseError::NotFound => write!(f, "Row not found"),
+            DatabaseError::UniqueViolation { constraint } => {
+                write!(f, "Unique constraint {} violated", constraint.as_deref().unwrap_or_default())
+            }
+            DatabaseError::ForeignKeyViolation { constraint } => {
+                write!(f, "Foreign key {} violated", constraint.as_deref().unwrap_or_default())
+            }
+            DatabaseError::SerializationFailure => write!(f, "Serialization failure"),
+            DatabaseError::Timeout => write!(f, "Database timeout"),
+            DatabaseError::PoolExhausted => write!(f, "Database pool exhausted"),
+            DatabaseError::Other(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for DatabaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DatabaseError::Other(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for DatabaseError {
+    fn from(error: sqlx::Error) -> Self {
+        match &error {
+            sqlx::Error::RowNotFound => DatabaseError::NotFound,
+            sqlx::Error::PoolTimedOut => DatabaseError::PoolExhausted,
+            sqlx::Error::Io(io) if io.kind() == std::io::ErrorKind::TimedOut => DatabaseError::Timeout,
+            sqlx::Error::Database(database) => {
+                let constraint = database.constraint().map(|constraint| constraint.to_string());
+                let code = database.code().unwrap_or_default();
+
+                if database.is_unique_violation() {
+                    DatabaseError::UniqueViolation { constraint }
+                } else if database.is_foreign_key_violation() {
+                    DatabaseError::ForeignKeyViolation { constraint }
+                } else if SERIALIZATION_FAILURE_CODES.contains(&code.as_ref()) {
+                    DatabaseError::SerializationFailure
+                } else if TIMEOUT_CODES.contains(&code.as_ref()) {
+                    DatabaseError::Timeout
+                } else {
+                    DatabaseError::Other(error)
+                }
+            }
+            _ => DatabaseError::Other(error),
+        }
+    }
+}
+
+/// Status codes of the [`DatabaseError`]s, set through
+/// [`PostgresDatabase::with_error_status`](crate::databasex::PostgresDatabase::with_error_status).
+#[derive(Debug, Clone, Copy)]
+pub struct DatabaseErrorStatus {
+    pub not_found: StatusCode,
+    pub unique_violation: StatusCode,
+    pub foreign_key_violation: StatusCode,
+    pub serialization_failure: StatusCode,
+    pub timeout: StatusCode,
+    pub pool_exhausted: StatusCode,
+    pub other: StatusCode,
+}
+
+impl Default for DatabaseErrorStatus {
+    fn default() -> Self {
+        Self {
+            not_found: StatusCode::NOT_FOUND,
+            unique_violation: StatusCode::CONFLICT,
+            foreign_key_violation: StatusCode::CONFLICT,
+            serialization_failure: StatusCode::SERVICE_UNAVAILABLE,
+            timeout: StatusCode::SERVICE_UNAVAILABLE,
+            pool_exhausted: StatusCode::SERVICE_UNAVAILABLE,
+            other: StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl DatabaseErrorStatus {
+    pub fn status_code(&self, error: &DatabaseError) -> StatusCode {
+        match error {
+            DatabaseError::NotFound => self.not_found,
+            DatabaseError::UniqueViolation { .. } => self.unique_violation,
+            DatabaseError::ForeignKeyViolation { .. } => self.foreign_key_violation,
+            DatabaseError::SerializationFailure => self.serialization_failure,
+            DatabaseError::Timeout => self.timeout,
+            DatabaseError::PoolExhausted => self.pool_exhausted,
+            DatabaseError::Other(_) => self.other,
+        }
+    }
+
+    /// [`HttpError`] of a failed database call, keeping the [`DatabaseError`] as its source.
+    pub(crate) fn http_error(&self, error_message: String, error: sqlx::Error, tags: &HttpTags) -> HttpError {
+        let error = DatabaseError::from(error);
+
+        HttpError::without_body(self.status_code(&error), error_message, tags.clone()).with_source(error)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::httpx::HttpResponse;
+
+    #[test]
+    fn should_map_database_errors_to_status_codes() {
+        let status = DatabaseErrorStatus::default();
+        let tags = HttpTags::default();
+
+        let not_found = status.http_error("Failed to fetch order".to_string(), sqlx::Error::RowNotFound, &tags);
+        assert_eq!(not_found.status_code(), StatusCode::NOT_FOUND);
+        assert!(matches!(not_found.source_as::<DatabaseError>(), Some(DatabaseError::NotFound)));
+        assert_eq!(
+            not_found.error_message().unwrap(),
+            "Failed to fetch order"
+        );
+
+        let exhausted = status.http_error("Failed to acquire connection".to_string(), sqlx::Error::PoolTimedOut, &tags);
+        assert_eq!(exhausted.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let other = status.http_error(
+            "Failed to decode".to_string(),
+            sqlx::Error::ColumnNotFound("id".to_string()),
+            &tags,
+        );
+        assert_eq!(other.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(matches!(other.source_as::<DatabaseError>(), Some(DatabaseError::Other(_))));
+    }
+
+    #[test]
+    fn should_override_status_codes() {
+        let status = DatabaseErrorStatus {
+            not_found: StatusCode::UNPROCESSABLE_ENTITY,
+            ..DatabaseErrorStatus::default()
+        };
+
+        assert_eq!(status.status_code(&DatabaseError::NotFound), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            status.status_code(&DatabaseError::UniqueViolation { constraint: None }),
+            StatusCode::CONFLICT
+        );
+    }
+}
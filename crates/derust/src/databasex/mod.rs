@@ -1,7 +1,10 @@
+mod error;
 mod repository;
 
 #[cfg(any(feature = "postgres", feature = "outbox"))]
 mod postgresx;
 
+pub use error::*;
 pub use postgresx::database::*;
+pub(crate) use postgresx::health::PostgresHealthIndicator;
 pub use repository::*;
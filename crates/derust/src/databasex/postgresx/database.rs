@@ -1,7 +1,7 @@
+use crate::databasex::DatabaseErrorStatus;
 use crate::httpx::{AppContext, HttpError, HttpTags};
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 use crate::metricx::{timer, MetricTags, Stopwatch};
-use axum::http::StatusCode;
 use serde::Deserialize;
 use sqlx::pool::PoolConnection;
 use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
@@ -12,6 +12,7 @@ use std::fmt::{Debug, Formatter};
 pub struct PostgresDatabase {
     pub read_write: Pool<Postgres>,
     pub read_only: Option<Pool<Postgres>>,
+    error_status: DatabaseErrorStatus,
 }
 
 impl PostgresDatabase {
@@ -45,6 +46,28 @@ impl PostgresDatabase {
         create_database(&database).await
     }
 
+    /// Pool connecting on first use, for tests not reaching the database.
+    #[cfg(test)]
+    pub(crate) fn connect_lazy(url: &str) -> Result<PostgresDatabase, Error> {
+        Ok(PostgresDatabase {
+            read_write: PgPoolOptions::new()
+                .acquire_timeout(std::time::Duration::from_millis(50))
+                .connect_lazy(url)?,
+            read_only: None,
+            error_status: DatabaseErrorStatus::default(),
+        })
+    }
+
+    /// Replaces the status codes of the errors of this database and its repositories.
+    pub fn with_error_status(mut self, error_status: DatabaseErrorStatus) -> Self {
+        self.error_status = error_status;
+        self
+    }
+
+    pub fn error_status(&self) -> &DatabaseErrorStatus {
+        &self.error_status
+    }
+
     pub async fn get_connection(
         &self,
         read_only: bool,
@@ -61,15 +84,15 @@ impl PostgresDatabase {
         };
 
         pool.acquire().await.map_err(|error| {
-            HttpError::without_body(
-                StatusCode::INTERNAL_SERVER_ERROR,
+            self.error_status.http_error(
                 format!("Failed to acquire connection: {error}"),
-                tags.clone(),
+                error,
+                tags,
             )
         })
     }
 
-    #[cfg(any(feature = "statsd", feature = "prometheus"))]
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
     pub async fn begin_transaction<S>(
         &self,
         context: &AppContext<S>,
@@ -80,10 +103,10 @@ impl PostgresDatabase {
         S: Clone,
     {
         let transaction = self.read_write.begin().await.map_err(|error| {
-            HttpError::without_body(
-                StatusCode::INTERNAL_SERVER_ERROR,
+            self.error_status.http_error(
                 format!("Failed to begin transaction: {error}"),
-                tags.clone(),
+                error,
+                tags,
             )
         })?;
 
@@ -92,7 +115,8 @@ impl PostgresDatabase {
 
         Ok(PostgresTransaction {
             transaction,
-            #[cfg(any(feature = "statsd", feature = "prometheus"))]
+            error_status: self.error_status,
+            #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
             stopwatch: timer::start_stopwatch(
                 context,
                 "repository_transaction_seconds",
@@ -101,7 +125,7 @@ impl PostgresDatabase {
         })
     }
 
-    #[cfg(not(any(feature = "statsd", feature = "prometheus")))]
+    #[cfg(not(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics")))]
     pub async fn begin_transaction<S>(
         &self,
         context: &AppContext<S>,
@@ -111,47 +135,52 @@ impl PostgresDatabase {
         S: Clone,
     {
         let transaction = self.read_write.begin().await.map_err(|error| {
-            HttpError::without_body(
-                StatusCode::INTERNAL_SERVER_ERROR,
+            self.error_status.http_error(
                 format!("Failed to begin transaction: {error}"),
-                tags.clone(),
+                error,
+                tags,
             )
         })?;
 
-        Ok(PostgresTransaction { transaction })
+        Ok(PostgresTransaction {
+            transaction,
+            error_status: self.error_status,
+        })
     }
 }
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 pub struct PostgresTransaction<'a, S>
 where
     S: Clone,
 {
     pub transaction: Transaction<'a, Postgres>,
-    #[cfg(any(feature = "statsd", feature = "prometheus"))]
+    error_status: DatabaseErrorStatus,
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
     stopwatch: Stopwatch<S>,
 }
 
-#[cfg(not(any(feature = "statsd", feature = "prometheus")))]
+#[cfg(not(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics")))]
 pub struct PostgresTransaction<'a> {
     pub transaction: Transaction<'a, Postgres>,
+    error_status: DatabaseErrorStatus,
 }
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 impl<'a, S> PostgresTransaction<'a, S>
 where
     S: Clone,
 {
     pub async fn commit_transaction(self, tags: &HttpTags) -> Result<(), HttpError> {
         let result = self.transaction.commit().await.map_err(|error| {
-            HttpError::without_body(
-                StatusCode::INTERNAL_SERVER_ERROR,
+            self.error_status.http_error(
                 format!("Failed to commit transaction: {error}"),
-                tags.clone(),
+                error,
+                tags,
             )
         });
 
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         {
             let success = match result {
                 Ok(_) => "true",
@@ -168,14 +197,14 @@ where
     }
 }
 
-#[cfg(not(any(feature = "statsd", feature = "prometheus")))]
+#[cfg(not(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics")))]
 impl<'a> PostgresTransaction<'a> {
     pub async fn commit_transaction(self, tags: &HttpTags) -> Result<(), HttpError> {
         self.transaction.commit().await.map_err(|error| {
-            HttpError::without_body(
-                StatusCode::INTERNAL_SERVER_ERROR,
+            self.error_status.http_error(
                 format!("Failed to commit transaction: {error}"),
-                tags.clone(),
+                error,
+                tags,
             )
         })
     }
@@ -205,6 +234,7 @@ async fn create_database(database: &DatabaseConfig) -> Result<PostgresDatabase,
     Ok(PostgresDatabase {
         read_write,
         read_only,
+        error_status: DatabaseErrorStatus::default(),
     })
 }
 
@@ -0,0 +1,91 @@
+use crate::httpx::{AppContext, HealthCheck, HealthIndicator};
+use serde_json::json;
+use std::time::Duration;
+use tracing::error;
+
+/// Pings one of the [`PostgresDatabase`](crate::databasex::PostgresDatabase) pools.
+pub(crate) struct PostgresHealthIndicator {
+    read_only: bool,
+}
+
+impl PostgresHealthIndicator {
+    pub(crate) fn read_write() -> Self {
+        Self { read_only: false }
+    }
+
+    pub(crate) fn read_only() -> Self {
+        Self { read_only: true }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> HealthIndicator<S> for PostgresHealthIndicator
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn name(&self) -> &str {
+        if self.read_only {
+            "database_read_only"
+        } else {
+            "database"
+        }
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(100)
+    }
+
+    async fn check(&self, context: &AppContext<S>) -> HealthCheck {
+        let database = context.database();
+        let pool = match (self.read_only, &database.read_only) {
+            (true, Some(read_only)) => read_only,
+            _ => &database.read_write,
+        };
+
+        let details = json!({ "pool_size": pool.size(), "idle_connections": pool.num_idle() });
+
+        match sqlx::query_as::<_, (i32,)>("SELECT 1").fetch_one(pool).await {
+            Ok(_) => HealthCheck::ok().with_details(details),
+            Err(error) => {
+                // the error may hold the host or the user, so it is only logged
+                error!(
+                    "Health check of {} failed: {}",
+                    HealthIndicator::<S>::name(self),
+                    context.redaction().redact_text(&error.to_string())
+                );
+
+                HealthCheck::failure()
+                    .with_details(json!({ "error": "query failed", "pool_size": pool.size() }))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::httpx::{test_context, HealthStatus};
+
+    #[tokio::test]
+    async fn should_report_a_generic_failure() {
+        let context = test_context(()).await;
+        let indicator = PostgresHealthIndicator::read_write();
+
+        let check = HealthIndicator::<()>::check(&indicator, &context).await;
+
+        assert_eq!(check.status(), &HealthStatus::Failure);
+        assert_eq!(
+            serde_json::to_value(&check).unwrap()["details"]["error"],
+            "query failed"
+        );
+        assert!(HealthIndicator::<()>::critical(&indicator));
+        assert_eq!(
+            HealthIndicator::<()>::timeout(&indicator),
+            Duration::from_millis(100)
+        );
+        assert_eq!(
+            HealthIndicator::<()>::name(&PostgresHealthIndicator::read_only()),
+            "database_read_only"
+        );
+    }
+}
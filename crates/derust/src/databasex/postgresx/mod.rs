@@ -1,9 +1,10 @@
 pub mod database;
+pub(crate) mod health;
 mod pg_connection_repository;
-#[cfg(not(any(feature = "statsd", feature = "prometheus")))]
+#[cfg(not(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics")))]
 mod pg_transaction_repository;
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 mod pg_transaction_repository_and_metrics;
 mod pg_option_transaction_repository;
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 mod pg_option_transaction_repository_and_metrics;
@@ -1,8 +1,7 @@
 use crate::databasex::repository::Repository;
 use crate::httpx::{AppContext, HttpError, HttpTags};
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 use crate::metricx::{timer, MetricTags, Stopwatch};
-use axum::http::StatusCode;
 use sqlx::query::{Query, QueryAs, QueryScalar};
 use sqlx::{Database, FromRow, PgConnection, Postgres};
 
@@ -19,18 +18,18 @@ impl Repository<Postgres> for PgConnection {
         T: for<'r> FromRow<'r, <Postgres as Database>::Row> + Send + Unpin,
         S: Clone + Send + Sync,
     {
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         let stopwatch = stopwatch_start(context, query_name, tags);
 
         let result = query.fetch_one(&mut *self).await.map_err(|error| {
-            HttpError::without_body(
-                StatusCode::INTERNAL_SERVER_ERROR,
+            context.database().error_status().http_error(
                 format!("Failed to execute fetch one for {query_name} with error: {error}"),
-                tags.clone(),
+                error,
+                tags,
             )
         });
 
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         stopwatch_record(tags, stopwatch, result.is_ok());
 
         result
@@ -47,18 +46,18 @@ impl Repository<Postgres> for PgConnection {
         T: for<'r> FromRow<'r, <Postgres as Database>::Row> + Send + Unpin,
         S: Clone + Send + Sync,
     {
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         let stopwatch = stopwatch_start(context, query_name, tags);
 
         let result = query.fetch_optional(self).await.map_err(|error| {
-            HttpError::without_body(
-                StatusCode::INTERNAL_SERVER_ERROR,
+            context.database().error_status().http_error(
                 format!("Failed to execute fetch optional for {query_name} with error: {error}"),
-                tags.clone(),
+                error,
+                tags,
             )
         });
 
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         stopwatch_record(tags, stopwatch, result.is_ok());
 
         result
@@ -75,18 +74,18 @@ impl Repository<Postgres> for PgConnection {
         T: for<'r> FromRow<'r, <Postgres as Database>::Row> + Send + Unpin,
         S: Clone + Send + Sync,
     {
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         let stopwatch = stopwatch_start(context, query_name, tags);
 
         let result = query.fetch_all(self).await.map_err(|error| {
-            HttpError::without_body(
-                StatusCode::INTERNAL_SERVER_ERROR,
+            context.database().error_status().http_error(
                 format!("Failed to execute fetch all for {query_name} with error: {error}"),
-                tags.clone(),
+                error,
+                tags,
             )
         });
 
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         stopwatch_record(tags, stopwatch, result.is_ok());
 
         result
@@ -102,18 +101,18 @@ impl Repository<Postgres> for PgConnection {
     where
         S: Clone + Send + Sync,
     {
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         let stopwatch = stopwatch_start(context, query_name, tags);
 
         let result = query.fetch_one(self).await.map_err(|error| {
-            HttpError::without_body(
-                StatusCode::INTERNAL_SERVER_ERROR,
+            context.database().error_status().http_error(
                 format!("Failed to execute count for {query_name} with error: {error}"),
-                tags.clone(),
+                error,
+                tags,
             )
         });
 
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         stopwatch_record(tags, stopwatch, result.is_ok());
 
         Ok(result? as u64)
@@ -129,18 +128,18 @@ impl Repository<Postgres> for PgConnection {
     where
         S: Clone + Send + Sync,
     {
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         let stopwatch = stopwatch_start(context, query_name, tags);
 
         let result = query.fetch_one(self).await.map_err(|error| {
-            HttpError::without_body(
-                StatusCode::INTERNAL_SERVER_ERROR,
+            context.database().error_status().http_error(
                 format!("Failed to execute exists for {query_name} with error: {error}"),
-                tags.clone(),
+                error,
+                tags,
             )
         });
 
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         stopwatch_record(tags, stopwatch, result.is_ok());
 
         result
@@ -156,25 +155,25 @@ impl Repository<Postgres> for PgConnection {
     where
         S: Clone + Send + Sync,
     {
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         let stopwatch = stopwatch_start(context, query_name, tags);
 
         let result = query.execute(&mut *self).await.map_err(|error| {
-            HttpError::without_body(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to execute fetch one for {query_name} with error: {error}"),
-                tags.clone(),
+            context.database().error_status().http_error(
+                format!("Failed to execute {query_name} with error: {error}"),
+                error,
+                tags,
             )
         });
 
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         stopwatch_record(tags, stopwatch, result.is_ok());
 
-        Ok(())
+        result.map(|_| ())
     }
 }
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 fn stopwatch_start<S>(context: &AppContext<S>, query_name: &str, tags: &HttpTags) -> Stopwatch<S>
 where
     S: Clone + Send + Sync,
@@ -184,7 +183,7 @@ where
     timer::start_stopwatch(context, "repository_query_seconds", metric_tags)
 }
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 fn stopwatch_record<S>(tags: &HttpTags, stopwatch: Stopwatch<S>, success: bool)
 where
     S: Clone,
@@ -6,7 +6,7 @@ use sqlx::query::{Query, QueryAs, QueryScalar};
 use sqlx::{Database, FromRow, Postgres};
 
 #[async_trait::async_trait]
-#[cfg(not(any(feature = "statsd", feature = "prometheus")))]
+#[cfg(not(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics")))]
 impl Repository<Postgres> for Option<&mut PostgresTransaction<'_>> {
     async fn fetch_one<'a, S, T>(
         &mut self,
@@ -5,7 +5,7 @@ use sqlx::query::{Query, QueryAs, QueryScalar};
 use sqlx::{Database, FromRow, Postgres};
 
 #[async_trait::async_trait]
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 impl<SS: Clone + Send + Sync> Repository<Postgres> for Option<&mut PostgresTransaction<'_, SS>> {
     async fn fetch_one<'a, S, T>(
         &mut self,
@@ -1,12 +1,11 @@
 use crate::databasex::repository::Repository;
 use crate::databasex::PostgresTransaction;
 use crate::httpx::{AppContext, HttpError, HttpTags};
-use axum::http::StatusCode;
 use sqlx::query::{Query, QueryAs, QueryScalar};
 use sqlx::{Database, FromRow, Postgres};
 
 #[async_trait::async_trait]
-#[cfg(not(any(feature = "statsd", feature = "prometheus")))]
+#[cfg(not(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics")))]
 impl Repository<Postgres> for PostgresTransaction<'_> {
     async fn fetch_one<'a, S, T>(
         &mut self,
@@ -23,10 +22,10 @@ impl Repository<Postgres> for PostgresTransaction<'_> {
             .fetch_one(&mut *self.transaction)
             .await
             .map_err(|error| {
-                HttpError::without_body(
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                context.database().error_status().http_error(
                     format!("Failed to execute fetch one for {query_name} with error: {error}"),
-                    tags.clone(),
+                    error,
+                    tags,
                 )
             });
 
@@ -48,12 +47,12 @@ impl Repository<Postgres> for PostgresTransaction<'_> {
             .fetch_optional(&mut *self.transaction)
             .await
             .map_err(|error| {
-                HttpError::without_body(
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                context.database().error_status().http_error(
                     format!(
                         "Failed to execute fetch optional for {query_name} with error: {error}"
                     ),
-                    tags.clone(),
+                    error,
+                    tags,
                 )
             });
 
@@ -75,10 +74,10 @@ impl Repository<Postgres> for PostgresTransaction<'_> {
             .fetch_all(&mut *self.transaction)
             .await
             .map_err(|error| {
-                HttpError::without_body(
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                context.database().error_status().http_error(
                     format!("Failed to execute fetch all for {query_name} with error: {error}"),
-                    tags.clone(),
+                    error,
+                    tags,
                 )
             });
 
@@ -99,10 +98,10 @@ impl Repository<Postgres> for PostgresTransaction<'_> {
             .fetch_one(&mut *self.transaction)
             .await
             .map_err(|error| {
-                HttpError::without_body(
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                context.database().error_status().http_error(
                     format!("Failed to execute count for {query_name} with error: {error}"),
-                    tags.clone(),
+                    error,
+                    tags,
                 )
             });
 
@@ -123,10 +122,10 @@ impl Repository<Postgres> for PostgresTransaction<'_> {
             .fetch_one(&mut *self.transaction)
             .await
             .map_err(|error| {
-                HttpError::without_body(
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                context.database().error_status().http_error(
                     format!("Failed to execute exists for {query_name} with error: {error}"),
-                    tags.clone(),
+                    error,
+                    tags,
                 )
             });
 
@@ -143,13 +142,16 @@ impl Repository<Postgres> for PostgresTransaction<'_> {
     where
         S: Clone + Send + Sync,
     {
-        query.execute(&mut *self.transaction).await.map_err(|error| {
-            HttpError::without_body(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to execute fetch one for {query_name} with error: {error}"),
-                tags.clone(),
-            )
-        })?;
+        query
+            .execute(&mut *self.transaction)
+            .await
+            .map_err(|error| {
+                context.database().error_status().http_error(
+                    format!("Failed to execute {query_name} with error: {error}"),
+                    error,
+                    tags,
+                )
+            })?;
 
         Ok(())
     }
@@ -1,15 +1,14 @@
 use crate::databasex::repository::Repository;
 use crate::databasex::PostgresTransaction;
 use crate::httpx::{AppContext, HttpError, HttpTags};
-use axum::http::StatusCode;
 use sqlx::query::{Query, QueryAs, QueryScalar};
 use sqlx::{Database, FromRow, Postgres};
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 use crate::metricx::{timer, MetricTags, Stopwatch};
 
 #[async_trait::async_trait]
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 impl<SS: Clone + Send + Sync> Repository<Postgres> for PostgresTransaction<'_, SS> {
     async fn fetch_one<'a, S, T>(
         &mut self,
@@ -22,21 +21,21 @@ impl<SS: Clone + Send + Sync> Repository<Postgres> for PostgresTransaction<'_, S
         T: for<'r> FromRow<'r, <Postgres as Database>::Row> + Send + Unpin,
         S: Clone + Send + Sync,
     {
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         let stopwatch = stopwatch_start(context, query_name, tags);
 
         let result = query
             .fetch_one(&mut *self.transaction)
             .await
             .map_err(|error| {
-                HttpError::without_body(
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                context.database().error_status().http_error(
                     format!("Failed to execute fetch one for {query_name} with error: {error}"),
-                    tags.clone(),
+                    error,
+                    tags,
                 )
             });
 
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         stopwatch_record(tags, stopwatch, result.is_ok());
 
         result
@@ -53,23 +52,23 @@ impl<SS: Clone + Send + Sync> Repository<Postgres> for PostgresTransaction<'_, S
         T: for<'r> FromRow<'r, <Postgres as Database>::Row> + Send + Unpin,
         S: Clone + Send + Sync,
     {
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         let stopwatch = stopwatch_start(context, query_name, tags);
 
         let result = query
             .fetch_optional(&mut *self.transaction)
             .await
             .map_err(|error| {
-                HttpError::without_body(
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                context.database().error_status().http_error(
                     format!(
                         "Failed to execute fetch optional for {query_name} with error: {error}"
                     ),
-                    tags.clone(),
+                    error,
+                    tags,
                 )
             });
 
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         stopwatch_record(tags, stopwatch, result.is_ok());
 
         result
@@ -86,21 +85,21 @@ impl<SS: Clone + Send + Sync> Repository<Postgres> for PostgresTransaction<'_, S
         T: for<'r> FromRow<'r, <Postgres as Database>::Row> + Send + Unpin,
         S: Clone + Send + Sync,
     {
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         let stopwatch = stopwatch_start(context, query_name, tags);
 
         let result = query
             .fetch_all(&mut *self.transaction)
             .await
             .map_err(|error| {
-                HttpError::without_body(
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                context.database().error_status().http_error(
                     format!("Failed to execute fetch all for {query_name} with error: {error}"),
-                    tags.clone(),
+                    error,
+                    tags,
                 )
             });
 
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         stopwatch_record(tags, stopwatch, result.is_ok());
 
         result
@@ -116,21 +115,21 @@ impl<SS: Clone + Send + Sync> Repository<Postgres> for PostgresTransaction<'_, S
     where
         S: Clone + Send + Sync,
     {
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         let stopwatch = stopwatch_start(context, query_name, tags);
 
         let result = query
             .fetch_one(&mut *self.transaction)
             .await
             .map_err(|error| {
-                HttpError::without_body(
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                context.database().error_status().http_error(
                     format!("Failed to execute count for {query_name} with error: {error}"),
-                    tags.clone(),
+                    error,
+                    tags,
                 )
             });
 
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         stopwatch_record(tags, stopwatch, result.is_ok());
 
         Ok(result? as u64)
@@ -146,21 +145,21 @@ impl<SS: Clone + Send + Sync> Repository<Postgres> for PostgresTransaction<'_, S
     where
         S: Clone + Send + Sync,
     {
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         let stopwatch = stopwatch_start(context, query_name, tags);
 
         let result = query
             .fetch_one(&mut *self.transaction)
             .await
             .map_err(|error| {
-                HttpError::without_body(
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                context.database().error_status().http_error(
                     format!("Failed to execute exists for {query_name} with error: {error}"),
-                    tags.clone(),
+                    error,
+                    tags,
                 )
             });
 
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         stopwatch_record(tags, stopwatch, result.is_ok());
 
         result
@@ -176,25 +175,28 @@ impl<SS: Clone + Send + Sync> Repository<Postgres> for PostgresTransaction<'_, S
     where
         S: Clone + Send + Sync,
     {
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         let stopwatch = stopwatch_start(context, query_name, tags);
 
-        let result = query.execute(&mut *self.transaction).await.map_err(|error| {
-            HttpError::without_body(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to execute fetch one for {query_name} with error: {error}"),
-                tags.clone(),
-            )
-        });
+        let result = query
+            .execute(&mut *self.transaction)
+            .await
+            .map_err(|error| {
+                context.database().error_status().http_error(
+                    format!("Failed to execute {query_name} with error: {error}"),
+                    error,
+                    tags,
+                )
+            });
 
-        #[cfg(any(feature = "statsd", feature = "prometheus"))]
+        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
         stopwatch_record(tags, stopwatch, result.is_ok());
 
-        Ok(())
+        result.map(|_| ())
     }
 }
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 fn stopwatch_start<S>(context: &AppContext<S>, query_name: &str, tags: &HttpTags) -> Stopwatch<S>
 where
     S: Clone + Send + Sync,
@@ -204,7 +206,7 @@ where
     timer::start_stopwatch(context, "repository_query_seconds", metric_tags)
 }
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 fn stopwatch_record<S>(tags: &HttpTags, stopwatch: Stopwatch<S>, success: bool)
 where
     S: Clone,
@@ -9,6 +9,7 @@ const STAGING: &str = "staging";
 const PRODUCTION: &str = "production";
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String")]
 pub enum Environment {
     Local,
     Test,
@@ -0,0 +1,77 @@
+use crate::httpx::{AppContext, HealthCheck, HealthIndicator};
+use growthbook_rust_sdk::client::GrowthBookClientTrait;
+use serde_json::json;
+use std::time::Duration;
+
+/// Reports how long ago the GrowthBook features were refreshed. Stale features
+/// are still served, so it is not critical.
+pub(crate) struct GrowthBookHealthIndicator {
+    max_refresh_age: Duration,
+}
+
+impl GrowthBookHealthIndicator {
+    pub(crate) fn new(max_refresh_age: Duration) -> Self {
+        Self { max_refresh_age }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> HealthIndicator<S> for GrowthBookHealthIndicator
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn name(&self) -> &str {
+        "growth_book"
+    }
+
+    fn critical(&self) -> bool {
+        false
+    }
+
+    async fn check(&self, context: &AppContext<S>) -> HealthCheck {
+        let features = context.growth_book().total_features();
+        let age = context.growth_book_refresh().last_refresh().elapsed();
+        let details = json!({
+            "features": features,
+            "last_refresh_age_in_secs": age.as_secs(),
+            "max_refresh_age_in_secs": self.max_refresh_age.as_secs(),
+        });
+
+        if age > self.max_refresh_age {
+            HealthCheck::failure().with_details(details)
+        } else {
+            HealthCheck::ok().with_details(details)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::httpx::{test_context, HealthConfig, HealthStatus};
+    use tokio::time::Instant;
+
+    #[tokio::test]
+    async fn should_fail_once_the_features_are_older_than_the_max_age() {
+        let context = test_context(()).await;
+        let config = HealthConfig {
+            growth_book_max_refresh_age_in_secs: 60,
+            ..HealthConfig::default()
+        };
+        let indicator: &dyn HealthIndicator<()> = &GrowthBookHealthIndicator::new(
+            Duration::from_secs(config.growth_book_max_refresh_age_in_secs),
+        );
+
+        assert_eq!(indicator.check(&context).await.status(), &HealthStatus::Ok);
+
+        context
+            .growth_book_refresh()
+            .record(Instant::now() - Duration::from_secs(61));
+
+        assert_eq!(
+            indicator.check(&context).await.status(),
+            &HealthStatus::Failure
+        );
+        assert!(!indicator.critical());
+    }
+}
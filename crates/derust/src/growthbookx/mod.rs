@@ -1,9 +1,19 @@
+mod health;
+
 use crate::httpx::{HttpError, HttpTags};
 use axum::http::StatusCode;
-use serde_json::Value;
-use std::time::Duration;
 use growthbook_rust_sdk::client::GrowthBookClient;
 use growthbook_rust_sdk::model_public::GrowthBookAttribute;
+use serde_json::Value;
+use std::hash::BuildHasher;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+pub(crate) use health::GrowthBookHealthIndicator;
+
+const REFRESH_WATCH_INTERVAL: Duration = Duration::from_secs(1);
 
 pub struct GrowthBookConfig {
     pub growth_book_url: String,
@@ -35,5 +45,103 @@ pub fn growth_book_attributes(
             format!("Failed to parse growth book_attributes: {error}"),
             tags.clone(),
         )
+        .with_source(error)
     })
 }
+
+/// Time of the last successful features refresh of a client, kept by the
+/// [`AppContext`](crate::httpx::AppContext) of that client.
+#[derive(Clone)]
+pub(crate) struct FeaturesRefresh {
+    last_refresh: Arc<RwLock<Instant>>,
+}
+
+impl FeaturesRefresh {
+    /// The client loads its features when created, before the context.
+    pub(crate) fn new() -> Self {
+        Self {
+            last_refresh: Arc::new(RwLock::new(Instant::now())),
+        }
+    }
+
+    pub(crate) fn last_refresh(&self) -> Instant {
+        self.last_refresh
+            .read()
+            .map(|last_refresh| *last_refresh)
+            .unwrap_or_else(|error| *error.into_inner())
+    }
+
+    fn record(&self, at: Instant) {
+        if let Ok(mut last_refresh) = self.last_refresh.write() {
+            *last_refresh = at;
+        }
+    }
+}
+
+/// Records every features refresh of the SDK until the shutdown.
+pub(crate) async fn watch_refresh(
+    client: GrowthBookClient,
+    refresh: FeaturesRefresh,
+    shutdown: CancellationToken,
+) {
+    let mut fingerprint = features_fingerprint(&client);
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = tokio::time::sleep(REFRESH_WATCH_INTERVAL) => {}
+        }
+
+        let current = features_fingerprint(&client);
+        if current != fingerprint {
+            fingerprint = current;
+            refresh.record(Instant::now());
+        }
+    }
+}
+
+/// Tells the features map apart from the previous one: the SDK only replaces
+/// it on a successful refresh, and every new map gets its own hasher keys.
+fn features_fingerprint(client: &GrowthBookClient) -> Option<u64> {
+    client
+        .gb
+        .read()
+        .ok()
+        .map(|growth_book| growth_book.features.hasher().hash_one(0u8))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::httpx::test_context;
+
+    #[tokio::test]
+    async fn should_tell_refreshed_features_apart() {
+        let context = test_context(()).await;
+        let client = context.growth_book().clone();
+        let fingerprint = features_fingerprint(&client);
+
+        assert_eq!(features_fingerprint(&client), fingerprint);
+
+        // what the SDK does on a successful refresh, even with the same features
+        client.gb.write().unwrap().features = serde_json::from_str("{}").unwrap();
+        assert_ne!(features_fingerprint(&client), fingerprint);
+    }
+
+    #[tokio::test]
+    async fn should_stop_watching_on_shutdown() {
+        let context = test_context(()).await;
+        let shutdown = CancellationToken::new();
+        let watcher = tokio::spawn(watch_refresh(
+            context.growth_book().clone(),
+            FeaturesRefresh::new(),
+            shutdown.clone(),
+        ));
+
+        shutdown.cancel();
+
+        assert!(tokio::time::timeout(Duration::from_millis(100), watcher)
+            .await
+            .is_ok());
+    }
+}
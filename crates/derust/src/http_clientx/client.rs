@@ -1,4 +1,4 @@
-use crate::httpx::{AppContext, HttpError, HttpTags};
+use crate::httpx::{current_request_id, AppContext, HttpError, HttpTags, REQUEST_ID_HEADER};
 use opentelemetry::trace::TraceContextExt;
 use reqwest::{Client, Method, StatusCode};
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, RequestBuilder};
@@ -8,7 +8,7 @@ use std::time::Duration;
 use tracing::Span;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 use crate::metricx::{timer, MetricTags, Stopwatch};
 
 #[derive(Clone)]
@@ -205,7 +205,18 @@ where
         request_builder = request_builder.form(values);
     }
 
-    for (key, value) in headers.unwrap_or_default() {
+    let headers = headers.unwrap_or_default();
+
+    let has_request_id = headers
+        .iter()
+        .any(|(key, _)| key.eq_ignore_ascii_case(REQUEST_ID_HEADER));
+    if !has_request_id {
+        if let Some(request_id) = current_request_id() {
+            request_builder = request_builder.header(REQUEST_ID_HEADER, request_id);
+        }
+    }
+
+    for (key, value) in headers {
         request_builder = request_builder.header(key, value);
     }
 
@@ -213,7 +224,7 @@ where
         request_builder = request_builder.header("traceparent", &trace_parent);
     }
 
-    #[cfg(any(feature = "statsd", feature = "prometheus"))]
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
     let stopwatch = start_stopwatch(&context, request_context);
 
     let res = request_builder.send().await.map_err(|error| {
@@ -222,11 +233,12 @@ where
             format!("Failed to send http request: {error}"),
             tags.clone(),
         )
+        .with_source(error)
     })?;
 
     let status_code = res.status();
 
-    #[cfg(any(feature = "statsd", feature = "prometheus"))]
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
     stopwatch.record(MetricTags::from([(
         "status",
         status_code.as_u16().to_string(),
@@ -239,6 +251,7 @@ where
                 format!("Failed to deserialize response: {error}"),
                 tags.clone(),
             )
+            .with_source(error)
         })?;
 
         Ok(Response {
@@ -266,7 +279,8 @@ where
                 status_code,
                 format!("Failed to get http response error: {error}"),
                 tags.clone(),
-            ),
+            )
+            .with_source(error),
         };
 
         Err(response)
@@ -315,7 +329,7 @@ fn get_traceparent() -> Option<String> {
     }
 }
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 fn start_stopwatch<S>(context: &AppContext<S>, req: RequestContext) -> Stopwatch<S>
 where
     S: Clone,
@@ -330,7 +344,7 @@ mod test {
     use crate::http_clientx::client::RequestContext;
 
     #[test]
-    #[cfg(any(feature = "statsd", feature = "prometheus"))]
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
     fn should_remove_params_and_split_path_from_url() {
         let urls_and_paths = vec![
             ("https://www.rust-lang.org", ""),
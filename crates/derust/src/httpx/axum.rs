@@ -1,31 +1,57 @@
+use crate::httpx::bytes::BytesResponse;
+use crate::httpx::empty::EmptyResponse;
+use crate::httpx::file::FileResponse;
 use crate::httpx::json::JsonResponse;
+use crate::httpx::redirect::RedirectResponse;
+use crate::httpx::sse::SseResponse;
 use crate::httpx::text::TextResponse;
-use crate::httpx::{HttpError, HttpResponse};
-use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use crate::httpx::{HttpError, HttpResponse, HttpTags, ResponseError};
+use axum::http::{HeaderName, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
-use std::str::FromStr;
-
-impl IntoResponse for Box<dyn HttpResponse> {
-    fn into_response(self) -> Response {
-        let headers_vec = self.response_headers().unwrap_or_default();
-
-        let mut headers = HeaderMap::new();
-        for (name, value) in headers_vec {
-            headers.insert(
-                HeaderName::from_str(&name).unwrap(),
-                HeaderValue::from_str(&value).unwrap(),
-            );
+use axum_tracing_opentelemetry::tracing_opentelemetry_instrumentation_sdk::find_current_trace_id;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Builds the response, failing on header names or values that aren't valid HTTP.
+fn build_response(response: &mut dyn HttpResponse) -> Result<Response, HttpError> {
+    let mut res = Response::new(response.take_body());
+    let mut names = HashSet::new();
+
+    for (name, value) in response.response_headers().unwrap_or_default() {
+        let header_name =
+            HeaderName::try_from(name.as_str()).map_err(|error| invalid_header(&name, error))?;
+        let header_value =
+            HeaderValue::try_from(value.as_str()).map_err(|error| invalid_header(&name, error))?;
+
+        // repeating a name, such as `Set-Cookie`, sends every value
+        if names.insert(header_name.clone()) {
+            res.headers_mut().insert(header_name, header_value);
+        } else {
+            res.headers_mut().append(header_name, header_value);
         }
+    }
+
+    *res.status_mut() = response.status_code();
+    res.extensions_mut().insert(response.tags());
 
-        let mut response = Response::builder()
-            .status(self.status_code())
-            .body(self.response_body().unwrap_or_default().into())
-            .unwrap();
+    Ok(res)
+}
 
-        *response.headers_mut() = headers;
-        response.extensions_mut().insert(self.tags());
+fn invalid_header<E>(name: &str, error: E) -> HttpError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    HttpError::without_body(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("Invalid response header {name}: {error}"),
+        HttpTags::default(),
+    )
+    .with_source(error)
+}
 
-        response
+impl IntoResponse for Box<dyn HttpResponse> {
+    fn into_response(mut self) -> Response {
+        build_response(self.as_mut()).unwrap_or_else(IntoResponse::into_response)
     }
 }
 
@@ -33,73 +59,142 @@ impl<T> IntoResponse for JsonResponse<T>
 where
     T: serde::Serialize + Send + Sync,
 {
-    fn into_response(self) -> Response {
-        let headers_vec = self.response_headers().unwrap_or_default();
-
-        let mut headers = HeaderMap::new();
-        for (name, value) in headers_vec {
-            headers.insert(
-                HeaderName::from_str(&name).unwrap(),
-                HeaderValue::from_str(&value).unwrap(),
-            );
-        }
-
-        let mut response = Response::builder()
-            .status(self.status_code())
-            .body(self.response_body().unwrap_or_default().into())
-            .unwrap();
+    fn into_response(mut self) -> Response {
+        build_response(&mut self).unwrap_or_else(IntoResponse::into_response)
+    }
+}
 
-        *response.headers_mut() = headers;
-        response.extensions_mut().insert(self.tags());
+impl IntoResponse for TextResponse {
+    fn into_response(mut self) -> Response {
+        build_response(&mut self).unwrap_or_else(IntoResponse::into_response)
+    }
+}
 
-        response
+impl IntoResponse for BytesResponse {
+    fn into_response(mut self) -> Response {
+        build_response(&mut self).unwrap_or_else(IntoResponse::into_response)
     }
 }
 
-impl IntoResponse for TextResponse {
-    fn into_response(self) -> Response {
-        let headers_vec = self.response_headers().unwrap_or_default();
-
-        let mut headers = HeaderMap::new();
-        for (name, value) in headers_vec {
-            headers.insert(
-                HeaderName::from_str(&name).unwrap(),
-                HeaderValue::from_str(&value).unwrap(),
-            );
-        }
+impl IntoResponse for FileResponse {
+    fn into_response(mut self) -> Response {
+        build_response(&mut self).unwrap_or_else(IntoResponse::into_response)
+    }
+}
 
-        let mut response = Response::builder()
-            .status(self.status_code())
-            .body(self.response_body().unwrap_or_default().into())
-            .unwrap();
+impl IntoResponse for SseResponse {
+    fn into_response(mut self) -> Response {
+        build_response(&mut self).unwrap_or_else(IntoResponse::into_response)
+    }
+}
 
-        *response.headers_mut() = headers;
-        response.extensions_mut().insert(self.tags());
+impl IntoResponse for RedirectResponse {
+    fn into_response(mut self) -> Response {
+        build_response(&mut self).unwrap_or_else(IntoResponse::into_response)
+    }
+}
 
-        response
+impl IntoResponse for EmptyResponse {
+    fn into_response(mut self) -> Response {
+        build_response(&mut self).unwrap_or_else(IntoResponse::into_response)
     }
 }
 
 impl IntoResponse for HttpError {
-    fn into_response(self) -> Response {
-        let headers_vec = self.response_headers().unwrap_or_default();
-
-        let mut headers = HeaderMap::new();
-        for (name, value) in headers_vec {
-            headers.insert(
-                HeaderName::from_str(&name).unwrap(),
-                HeaderValue::from_str(&value).unwrap(),
-            );
+    fn into_response(mut self) -> Response {
+        match build_response(&mut self) {
+            Ok(mut response) => {
+                response.extensions_mut().insert(ResponseError {
+                    error: Arc::new(self),
+                    trace_id: find_current_trace_id(),
+                });
+
+                response
+            }
+            // the invalid header error has none, so this ends
+            Err(error) => error.into_response(),
         }
+    }
+}
 
-        let mut response = Response::builder()
-            .status(self.status_code())
-            .body(self.response_body().unwrap_or_default().into())
-            .unwrap();
+#[cfg(test)]
+mod test {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    async fn body(response: Response) -> String {
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8_lossy(&body).to_string()
+    }
 
-        *response.headers_mut() = headers;
-        response.extensions_mut().insert(self.tags());
+    #[tokio::test]
+    async fn should_answer_invalid_headers_with_500() {
+        let response = TextResponse::new(StatusCode::OK, "ok".to_string(), HttpTags::default())
+            .with_headers(vec![("x-name".to_string(), "line\nbreak".to_string())])
+            .into_response();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let error = &response.extensions().get::<ResponseError>().unwrap().error;
+        assert!(error
+            .to_string()
+            .starts_with("Invalid response header x-name"));
+
+        let response = EmptyResponse::new(StatusCode::NO_CONTENT, HttpTags::default())
+            .with_headers(vec![("bad header".to_string(), "value".to_string())])
+            .into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn should_send_binary_and_redirect_responses() {
+        let response = BytesResponse::from_stream(
+            StatusCode::OK,
+            futures_util::stream::iter(vec![Ok::<_, std::io::Error>("a"), Ok("b")]),
+            HttpTags::default(),
+        )
+        .with_content_type("image/png")
+        .into_response();
+
+        assert_eq!(response.headers()["content-type"], "image/png");
+        assert_eq!(body(response).await, "ab");
+
+        let response = RedirectResponse::to("/orders/1", HttpTags::default()).into_response();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(response.headers()["location"], "/orders/1");
+
+        let response = RedirectResponse::to("/orders/1", HttpTags::default())
+            .with_headers(vec![("location".to_string(), "/orders/2".to_string())])
+            .into_response();
+        assert_eq!(response.headers().get_all("location").iter().count(), 1);
+    }
 
-        response
+    #[tokio::test]
+    async fn should_send_an_overridden_content_type_once() {
+        let response = HttpError::with_json(
+            StatusCode::BAD_REQUEST,
+            "Invalid order".to_string(),
+            serde_json::json!({"message": "Invalid order"}),
+            HttpTags::default(),
+        )
+        .with_headers(vec![(
+            "content-type".to_string(),
+            "application/vnd.api+json".to_string(),
+        )])
+        .into_response();
+
+        let content_types: Vec<_> = response.headers().get_all("content-type").iter().collect();
+        assert_eq!(content_types, vec!["application/vnd.api+json"]);
+
+        let response = TextResponse::new(StatusCode::OK, "ok".to_string(), HttpTags::default())
+            .with_headers(vec![
+                ("Content-Type".to_string(), "text/csv".to_string()),
+                ("Set-Cookie".to_string(), "a=1".to_string()),
+                ("Set-Cookie".to_string(), "b=2".to_string()),
+            ])
+            .into_response();
+
+        assert_eq!(response.headers().get_all("content-type").iter().count(), 1);
+        assert_eq!(response.headers()["content-type"], "text/csv");
+        assert_eq!(response.headers().get_all("set-cookie").iter().count(), 2);
     }
 }
@@ -0,0 +1,227 @@
+#[cfg(feature = "tls")]
+use crate::httpx::TlsListener;
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::os::unix::fs::FileTypeExt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use tokio::net::{TcpListener, UnixListener};
+
+const DUAL_STACK: &str = "dual_stack";
+const UNIX_PREFIX: &str = "unix:";
+const LISTEN_BACKLOG: i32 = 1024;
+
+/// Where the HTTP server listens, loadable through `envx::load_app_config` as
+/// an IP (`0.0.0.0`, `::1`), `dual_stack` or `unix:/path/to/socket`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum BindAddress {
+    /// A single IPv4 or IPv6 address. IPv6 addresses only accept IPv6 clients,
+    /// whatever the OS default, see [`BindAddress::DualStack`] for both.
+    Ip(IpAddr),
+    /// Every IPv6 and IPv4 address of the host.
+    DualStack,
+    /// Unix domain socket, usually shared with a sidecar. The port is ignored.
+    Unix(PathBuf),
+}
+
+impl Default for BindAddress {
+    fn default() -> Self {
+        BindAddress::Ip(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+    }
+}
+
+impl Display for BindAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindAddress::Ip(ip) => write!(f, "{ip}"),
+            BindAddress::DualStack => write!(f, "{DUAL_STACK}"),
+            BindAddress::Unix(path) => write!(f, "{UNIX_PREFIX}{}", path.display()),
+        }
+    }
+}
+
+impl FromStr for BindAddress {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value == DUAL_STACK {
+            return Ok(BindAddress::DualStack);
+        }
+
+        if let Some(path) = value.strip_prefix(UNIX_PREFIX) {
+            return Ok(BindAddress::Unix(PathBuf::from(path)));
+        }
+
+        value
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .map(BindAddress::Ip)
+            .map_err(|_| format!("Invalid bind address {value}, expected an IP, `{DUAL_STACK}` or `{UNIX_PREFIX}<path>`"))
+    }
+}
+
+impl TryFrom<String> for BindAddress {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<BindAddress> for String {
+    fn from(value: BindAddress) -> Self {
+        value.to_string()
+    }
+}
+
+impl BindAddress {
+    /// Address of the management server: the same IP as the HTTP server, or
+    /// the IPv4 loopback when the HTTP server listens on a Unix socket, so
+    /// health and metrics aren't exposed beyond the host it is shared with.
+    pub(crate) fn for_management(&self) -> BindAddress {
+        match self {
+            BindAddress::Unix(_) => BindAddress::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            other => other.clone(),
+        }
+    }
+
+    pub(crate) fn describe(&self, port: u16) -> String {
+        match self {
+            BindAddress::Ip(ip) => SocketAddr::from((*ip, port)).to_string(),
+            BindAddress::DualStack => format!("[::]:{port} (dual stack)"),
+            BindAddress::Unix(path) => path.display().to_string(),
+        }
+    }
+
+    pub(crate) async fn bind(&self, port: u16) -> io::Result<ServerListener> {
+        let result = match self {
+            BindAddress::Ip(IpAddr::V4(ip)) => TcpListener::bind(SocketAddr::from((*ip, port)))
+                .await
+                .map(ServerListener::Tcp),
+            BindAddress::Ip(IpAddr::V6(ip)) => bind_ipv6(*ip, port, true).map(ServerListener::Tcp),
+            BindAddress::DualStack => bind_ipv6(Ipv6Addr::UNSPECIFIED, port, false).map(ServerListener::Tcp),
+            BindAddress::Unix(path) => bind_unix(path).map(ServerListener::Unix),
+        };
+
+        result.map_err(|error| {
+            io::Error::new(error.kind(), format!("Failed to bind {}: {error}", self.describe(port)))
+        })
+    }
+}
+
+pub(crate) enum ServerListener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+    #[cfg(feature = "tls")]
+    Tls(TlsListener),
+}
+
+/// Sets `IPV6_V6ONLY` explicitly, since its OS default (`net.ipv6.bindv6only`
+/// on Linux) would otherwise decide whether IPv4 clients are accepted.
+fn bind_ipv6(ip: Ipv6Addr, port: u16, only_v6: bool) -> io::Result<TcpListener> {
+    let socket = Socket::new(Domain::IPV6, Type::STREAM, Some(Protocol::TCP))?;
+    socket.set_only_v6(only_v6)?;
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&SocketAddr::from((ip, port)).into())?;
+    socket.listen(LISTEN_BACKLOG)?;
+
+    TcpListener::from_std(socket.into())
+}
+
+/// Replaces a socket left behind by a previous run, but not one another
+/// process still listens on.
+fn bind_unix(path: &PathBuf) -> io::Result<UnixListener> {
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if metadata.file_type().is_socket() {
+            match std::os::unix::net::UnixStream::connect(path) {
+                Err(error) if error.kind() == io::ErrorKind::ConnectionRefused => std::fs::remove_file(path)?,
+                _ => return Err(io::Error::new(io::ErrorKind::AddrInUse, "socket already in use")),
+            }
+        }
+    }
+
+    UnixListener::bind(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_parse_bind_addresses() {
+        assert_eq!("0.0.0.0".parse(), Ok(BindAddress::default()));
+        assert_eq!("[::1]".parse(), Ok(BindAddress::Ip(IpAddr::V6(Ipv6Addr::LOCALHOST))));
+        assert_eq!("dual_stack".parse(), Ok(BindAddress::DualStack));
+        assert_eq!(
+            "unix:/tmp/app.sock".parse(),
+            Ok(BindAddress::Unix(PathBuf::from("/tmp/app.sock")))
+        );
+        assert!("localhost".parse::<BindAddress>().is_err());
+    }
+
+    #[test]
+    fn should_keep_the_management_server_on_loopback_for_unix_sockets() {
+        assert_eq!(
+            BindAddress::Unix(PathBuf::from("/tmp/app.sock")).for_management(),
+            BindAddress::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        );
+        assert_eq!(BindAddress::DualStack.for_management(), BindAddress::DualStack);
+    }
+
+    #[tokio::test]
+    async fn should_return_bind_failures() {
+        let taken = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = taken.local_addr().unwrap().port();
+
+        let error = BindAddress::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .bind(port)
+            .await
+            .err()
+            .unwrap();
+
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+        assert!(error.to_string().starts_with(&format!("Failed to bind 127.0.0.1:{port}")));
+    }
+
+    #[tokio::test]
+    async fn should_replace_only_stale_unix_sockets() {
+        let path = std::env::temp_dir().join(format!("derust-bind-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let address = BindAddress::Unix(path.clone());
+
+        let listener = address.bind(0).await.unwrap();
+        let error = address.bind(0).await.err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+
+        // dropping the listener leaves the socket file behind
+        drop(listener);
+        assert!(path.exists());
+        assert!(address.bind(0).await.is_ok());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn should_accept_ipv4_clients_only_on_dual_stack() {
+        let local_port = |listener: ServerListener| match listener {
+            ServerListener::Tcp(listener) => (listener.local_addr().unwrap().port(), listener),
+            _ => unreachable!(),
+        };
+
+        // hosts without IPv6 can't tell them apart
+        let Ok(ipv6_only) = BindAddress::Ip(IpAddr::V6(Ipv6Addr::UNSPECIFIED)).bind(0).await else {
+            return;
+        };
+        let (port, _ipv6_only) = local_port(ipv6_only);
+        assert!(tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_err());
+
+        let (port, _dual_stack) = local_port(BindAddress::DualStack.bind(0).await.unwrap());
+        assert!(tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_ok());
+    }
+}
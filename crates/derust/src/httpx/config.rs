@@ -1,3 +1,6 @@
+use crate::httpx::BindAddress;
+#[cfg(feature = "tls")]
+use crate::httpx::TlsConfig;
 use serde::{Deserialize, Serialize};
 
 pub const DEFAULT_PORT: u16 = 9011;
@@ -5,16 +8,42 @@ pub const DEFAULT_PORT: u16 = 9011;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     port: u16,
+    #[serde(default)]
+    bind_address: BindAddress,
+    #[serde(default)]
+    management_port: Option<u16>,
+    #[cfg(feature = "tls")]
+    #[serde(default)]
+    tls: Option<TlsConfig>,
 }
 
 impl Config {
     pub fn port(&self) -> u16 {
         self.port
     }
+
+    pub fn bind_address(&self) -> &BindAddress {
+        &self.bind_address
+    }
+
+    pub fn management_port(&self) -> Option<u16> {
+        self.management_port
+    }
+
+    #[cfg(feature = "tls")]
+    pub fn tls(&self) -> Option<&TlsConfig> {
+        self.tls.as_ref()
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
-        Self { port: DEFAULT_PORT }
+        Self {
+            port: DEFAULT_PORT,
+            bind_address: BindAddress::default(),
+            management_port: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
     }
 }
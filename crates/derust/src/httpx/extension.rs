@@ -1,49 +1,85 @@
 use crate::httpx::middlewares::log::{local_log_request, log_request};
-use crate::httpx::middlewares::{compression, error_handler, sensitive_headers, timeout};
+use crate::httpx::middlewares::{
+    compression, cors, error_handler, problem_details, request_id, sensitive_headers, timeout,
+};
+use crate::httpx::health::HealthIndicators;
 use crate::httpx::{health, AppContext};
-use axum::http::{header, HeaderName};
-use axum::routing::get;
 use axum::{middleware, Router};
 use axum_tracing_opentelemetry::middleware::{OtelAxumLayer, OtelInResponseLayer};
-use lazy_static::lazy_static;
-use std::sync::Arc;
 use tower_http::trace::TraceLayer;
 
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
+use crate::httpx::middlewares::http_metrics;
 #[cfg(feature = "prometheus")]
 use crate::httpx::prometheus;
+#[cfg(feature = "prometheus")]
+use axum::routing::get;
 
-lazy_static! {
-    static ref DEFAULT_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
-    static ref DEFAULT_SENSITIVE_HEADERS: Arc<[HeaderName]> =
-        vec![header::AUTHORIZATION, header::COOKIE].into();
-}
-
-pub(crate) fn apply_middlewares<S>(
-    router: Router<AppContext<S>>,
-    context: AppContext<S>,
-) -> Router<()>
+/// Operational endpoints: health and, with the `prometheus` feature, metrics.
+pub(crate) fn management_routes<S>(health_indicators: HealthIndicators<S>) -> Router<AppContext<S>>
 where
     S: Clone + Send + Sync + 'static,
 {
-    let mut builder = router.route(health::HEALTH_PATH, get(health::route));
+    #[allow(unused_mut)]
+    let mut router = health::routes(health_indicators);
 
     #[cfg(feature = "prometheus")]
     {
-        builder = builder.nest(
+        router = router.nest(
             prometheus::PROMETHEUS_METRICS_PATH,
             Router::new().route("/", get(prometheus::route)),
         );
     }
 
-    builder = builder
-        .layer(sensitive_headers::request_headers())
+    router
+}
+
+/// Minimal stack for the management listener: no request logging, timeout
+/// or compression, which are meant for the business API.
+pub(crate) fn apply_management_middlewares<S>(
+    router: Router<AppContext<S>>,
+    context: AppContext<S>,
+) -> Router<()>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router
+        .layer(sensitive_headers::request_headers(context.redaction()))
         .layer(error_handler::panic_catcher())
-        .layer(sensitive_headers::response_headers())
+        .layer(sensitive_headers::response_headers(context.redaction()))
+        .with_state(context)
+}
+
+pub(crate) fn apply_middlewares<S>(
+    router: Router<AppContext<S>>,
+    context: AppContext<S>,
+) -> Router<()>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let mut builder = router
+        .layer(sensitive_headers::request_headers(context.redaction()))
+        .layer(error_handler::panic_catcher())
+        .layer(sensitive_headers::response_headers(context.redaction()))
         .layer(TraceLayer::new_for_http())
         .layer(OtelInResponseLayer)
+        // inside the server span, so it's the current one
+        .layer(middleware::from_fn_with_state(
+            context.clone(),
+            error_handler::error_span::<S>,
+        ))
         .layer(OtelAxumLayer::default())
-        .layer(timeout::timeouts())
-        .layer(compression::compression());
+        .layer(middleware::from_fn_with_state(
+            timeout::timeout_duration(),
+            timeout::timeouts,
+        ))
+        // inside the compression, since the error body is replaced
+        .layer(middleware::from_fn_with_state(
+            context.clone(),
+            problem_details::problem_details::<S>,
+        ))
+        .layer(compression::compression())
+        .layer(middleware::from_fn_with_state(context.clone(), cors::cors::<S>));
 
     if context.env().is_local() {
         builder = builder.layer(middleware::from_fn_with_state(
@@ -57,5 +93,17 @@ where
         ));
     }
 
-    builder.with_state(context)
+    // outside the timeout and panic layers, to count what they produce
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
+    {
+        builder = builder.layer(middleware::from_fn_with_state(
+            context.clone(),
+            http_metrics::http_metrics::<S>,
+        ));
+    }
+
+    // outermost, so the request id is available to every other layer
+    builder
+        .layer(middleware::from_fn(request_id::request_id))
+        .with_state(context)
 }
@@ -1,91 +1,224 @@
-use std::error::Error;
-use std::time::Duration;
 use crate::httpx::json::JsonResponse;
 use crate::httpx::{AppContext, HttpError, HttpTags};
 use axum::extract::State;
 use axum::http::StatusCode;
-use opentelemetry_otlp::WithExportConfig;
-use serde::Serialize;
-use tokio::time::timeout;
+use axum::routing::get;
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{timeout, Instant};
+
+#[cfg(any(feature = "postgres", feature = "outbox"))]
+use crate::databasex::PostgresHealthIndicator;
+#[cfg(feature = "growthbook")]
+use crate::growthbookx::GrowthBookHealthIndicator;
 
 pub const HEALTH_PATH: &str = "/health";
+pub const LIVENESS_PATH: &str = "/health/liveness";
+pub const READINESS_PATH: &str = "/health/readiness";
+
+const DEFAULT_HEALTH_CHECK_TIMEOUT_IN_MILLIS: u64 = 1000;
+const DEFAULT_SQS_MAX_POLL_AGE_IN_SECS: u64 = 120;
+const DEFAULT_OUTBOX_MAX_DELAY_IN_SECS: u64 = 300;
+const DEFAULT_GROWTH_BOOK_MAX_REFRESH_AGE_IN_SECS: u64 = 300;
 
-#[derive(Serialize, PartialEq)]
+pub(crate) type HealthIndicators<S> = Vec<Arc<dyn HealthIndicator<S>>>;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum HealthStatus {
     Ok,
+    /// Only non-critical components are failing: the instance keeps receiving traffic.
+    Degraded,
     Failure,
+    Draining,
 }
 
 impl HealthStatus {
-    fn is_ok(&self) -> bool {
-        self == &HealthStatus::Ok
+    fn is_available(&self) -> bool {
+        matches!(self, HealthStatus::Ok | HealthStatus::Degraded)
     }
 }
 
-#[derive(Serialize)]
-pub struct HealthResponseDto {
+/// Result of a [`HealthIndicator`] check.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthCheck {
     status: HealthStatus,
-    #[cfg(feature = "postgres")]
-    database: DatabaseHealthResponseDto,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<Value>,
 }
 
-impl HealthResponseDto {
-    fn new(
-        #[cfg(feature = "postgres")]
-        database: DatabaseHealthResponseDto,
-    ) -> Self {
-        let mut status = HealthStatus::Ok;
+impl HealthCheck {
+    pub fn ok() -> Self {
+        Self {
+            status: HealthStatus::Ok,
+            details: None,
+        }
+    }
 
-        #[cfg(feature = "postgres")]
-        if !database.status.is_ok() {
-            status = HealthStatus::Failure;
+    pub fn degraded() -> Self {
+        Self {
+            status: HealthStatus::Degraded,
+            details: None,
         }
+    }
 
+    pub fn failure() -> Self {
         Self {
-            status,
-            #[cfg(feature = "postgres")]
-            database,
+            status: HealthStatus::Failure,
+            details: None,
         }
     }
+
+    /// Component specific information added to the readiness response, such as
+    /// a pool size or a lag.
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn status(&self) -> &HealthStatus {
+        &self.status
+    }
+}
+
+/// Check run by the readiness probe, registered through
+/// [`ServerBuilder::with_health_indicator`](crate::httpx::ServerBuilder::with_health_indicator).
+///
+/// A critical indicator failing (or not answering within its timeout) turns the
+/// instance unavailable (503), while a non-critical one only reports it as
+/// `DEGRADED`.
+#[async_trait::async_trait]
+pub trait HealthIndicator<S>: Send + Sync
+where
+    S: Clone + Send + Sync + 'static,
+{
+    /// Key of the component in the readiness response.
+    fn name(&self) -> &str;
+
+    fn critical(&self) -> bool {
+        true
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(DEFAULT_HEALTH_CHECK_TIMEOUT_IN_MILLIS)
+    }
+
+    async fn check(&self, context: &AppContext<S>) -> HealthCheck;
 }
 
-#[cfg(feature = "postgres")]
 #[derive(Serialize)]
-pub struct DatabaseHealthResponseDto {
+pub struct HealthResponseDto {
     status: HealthStatus,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    components: BTreeMap<String, ComponentHealthResponseDto>,
 }
 
-pub async fn route<S>(
-    State(state): State<AppContext<S>>,
-) -> Result<JsonResponse<HealthResponseDto>, HttpError>
+#[derive(Serialize)]
+pub struct ComponentHealthResponseDto {
+    status: HealthStatus,
+    critical: bool,
+    duration_in_millis: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<Value>,
+}
+
+/// Thresholds of the built-in health indicators, loadable through `envx::load_app_config`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    /// Maximum time since the last successful poll of each SQS consumer.
+    pub sqs_max_poll_age_in_secs: u64,
+    /// Maximum delay of the oldest unprocessed outbox message.
+    pub outbox_max_delay_in_secs: u64,
+    /// Maximum time since the last successful GrowthBook features refresh.
+    pub growth_book_max_refresh_age_in_secs: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            sqs_max_poll_age_in_secs: DEFAULT_SQS_MAX_POLL_AGE_IN_SECS,
+            outbox_max_delay_in_secs: DEFAULT_OUTBOX_MAX_DELAY_IN_SECS,
+            growth_book_max_refresh_age_in_secs: DEFAULT_GROWTH_BOOK_MAX_REFRESH_AGE_IN_SECS,
+        }
+    }
+}
+
+/// Indicators contributed by the integrations that don't depend on a server
+/// component: database pools and GrowthBook. Outbox and SQS indicators are
+/// registered by the server along with their components.
+pub(crate) fn builtin_indicators<S>(context: &AppContext<S>, config: &HealthConfig) -> HealthIndicators<S>
 where
     S: Clone + Send + Sync + 'static,
 {
-    #[cfg(feature = "postgres")]
-    let database = {
-        let result = timeout(
-            Duration::from_millis(100),
-            sqlx::query_as::<_, (i32,)>("SELECT 1").fetch_one(&state.database().read_write)
-        ).await;
-
-        let database_status = if result.is_ok() {
-            HealthStatus::Ok
-        } else {
-            HealthStatus::Failure
-        };
+    // each is only read by some of the features
+    let _ = (context, config);
 
-        DatabaseHealthResponseDto {
-            status: database_status,
-        }
+    let indicators: Vec<Option<Arc<dyn HealthIndicator<S>>>> = vec![
+        #[cfg(any(feature = "postgres", feature = "outbox"))]
+        Some(Arc::new(PostgresHealthIndicator::read_write())),
+        #[cfg(any(feature = "postgres", feature = "outbox"))]
+        context
+            .database()
+            .read_only
+            .as_ref()
+            .map(|_| Arc::new(PostgresHealthIndicator::read_only()) as Arc<dyn HealthIndicator<S>>),
+        #[cfg(feature = "growthbook")]
+        Some(Arc::new(GrowthBookHealthIndicator::new(Duration::from_secs(
+            config.growth_book_max_refresh_age_in_secs,
+        )))),
+    ];
+
+    indicators.into_iter().flatten().collect()
+}
+
+/// Liveness, readiness and the legacy `/health` route, which answers as the readiness probe.
+pub(crate) fn routes<S>(indicators: HealthIndicators<S>) -> Router<AppContext<S>>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let indicators = Arc::new(indicators);
+
+    let readiness = move |State(context): State<AppContext<S>>| {
+        let indicators = indicators.clone();
+        async move { readiness_route(context, indicators).await }
     };
 
-    let response = HealthResponseDto::new(
-        #[cfg(feature = "postgres")]
-        database,
-    );
+    Router::new()
+        .route(HEALTH_PATH, get(readiness.clone()))
+        .route(READINESS_PATH, get(readiness))
+        .route(LIVENESS_PATH, get(liveness_route))
+}
 
-    let http_status = if response.status.is_ok() {
+/// The process is up and serving requests, regardless of its dependencies.
+async fn liveness_route<S>(
+    State(_): State<AppContext<S>>,
+) -> Result<JsonResponse<HealthResponseDto>, HttpError>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let response = HealthResponseDto {
+        status: HealthStatus::Ok,
+        components: BTreeMap::new(),
+    };
+
+    Ok(JsonResponse::new(StatusCode::OK, response, HttpTags::default()))
+}
+
+async fn readiness_route<S>(
+    context: AppContext<S>,
+    indicators: Arc<HealthIndicators<S>>,
+) -> Result<JsonResponse<HealthResponseDto>, HttpError>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let response = readiness(&context, &indicators).await;
+
+    let http_status = if response.status.is_available() {
         StatusCode::OK
     } else {
         StatusCode::SERVICE_UNAVAILABLE
@@ -93,3 +226,153 @@ where
 
     Ok(JsonResponse::new(http_status, response, HttpTags::default()))
 }
+
+async fn readiness<S>(context: &AppContext<S>, indicators: &HealthIndicators<S>) -> HealthResponseDto
+where
+    S: Clone + Send + Sync + 'static,
+{
+    // checks run concurrently, each one bounded by its own timeout
+    let checks = indicators
+        .iter()
+        .map(|indicator| {
+            let name = indicator.name().to_string();
+            let critical = indicator.critical();
+            let indicator = indicator.clone();
+            let context = context.clone();
+            let handle = tokio::spawn(async move {
+                let started_at = Instant::now();
+                let check = match timeout(indicator.timeout(), indicator.check(&context)).await {
+                    Ok(check) => check,
+                    Err(_) => HealthCheck::failure().with_details(json!({
+                        "error": format!("timed out after {}ms", indicator.timeout().as_millis())
+                    })),
+                };
+                (check, started_at.elapsed())
+            });
+            (name, critical, handle)
+        })
+        .collect::<Vec<_>>();
+
+    let mut status = HealthStatus::Ok;
+    let mut components = BTreeMap::new();
+
+    for (name, critical, handle) in checks {
+        let (check, duration) = match handle.await {
+            Ok(result) => result,
+            Err(join_error) => (
+                HealthCheck::failure().with_details(json!({ "error": join_error.to_string() })),
+                Duration::ZERO,
+            ),
+        };
+
+        match check.status {
+            HealthStatus::Ok => {}
+            _ if critical && !check.status.is_available() => status = HealthStatus::Failure,
+            _ if status == HealthStatus::Ok => status = HealthStatus::Degraded,
+            _ => {}
+        }
+
+        components.insert(
+            name,
+            ComponentHealthResponseDto {
+                status: check.status,
+                critical,
+                duration_in_millis: duration.as_millis(),
+                details: check.details,
+            },
+        );
+    }
+
+    // reported during the pre-stop delay so the load balancer stops routing to this instance
+    if context.shutdown().is_draining() {
+        status = HealthStatus::Draining;
+    }
+
+    HealthResponseDto { status, components }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::httpx::test_context;
+
+    #[derive(Clone)]
+    struct AppState;
+
+    struct Indicator {
+        name: &'static str,
+        critical: bool,
+        check: HealthCheck,
+        delay: Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl HealthIndicator<AppState> for Indicator {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn critical(&self) -> bool {
+            self.critical
+        }
+
+        async fn check(&self, _context: &AppContext<AppState>) -> HealthCheck {
+            tokio::time::sleep(self.delay).await;
+            self.check.clone()
+        }
+    }
+
+    fn indicator(name: &'static str, critical: bool, check: HealthCheck, delay: u64) -> Arc<dyn HealthIndicator<AppState>> {
+        Arc::new(Indicator {
+            name,
+            critical,
+            check,
+            delay: Duration::from_millis(delay),
+        })
+    }
+
+    async fn context() -> AppContext<AppState> {
+        test_context(AppState).await
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_be_degraded_when_only_non_critical_components_fail() {
+        let indicators = vec![
+            indicator("cache", false, HealthCheck::failure(), 0),
+            indicator("database", true, HealthCheck::ok().with_details(json!({ "pool": 10 })), 0),
+        ];
+
+        let response = readiness(&context().await, &indicators).await;
+
+        assert_eq!(response.status, HealthStatus::Degraded);
+        assert_eq!(response.components["cache"].status, HealthStatus::Failure);
+        assert_eq!(response.components["database"].details, Some(json!({ "pool": 10 })));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_fail_when_a_critical_component_times_out() {
+        let indicators = vec![
+            indicator("cache", false, HealthCheck::ok(), 0),
+            indicator("database", true, HealthCheck::ok(), 5000),
+        ];
+
+        let response = readiness(&context().await, &indicators).await;
+
+        assert_eq!(response.status, HealthStatus::Failure);
+        assert_eq!(response.components["database"].status, HealthStatus::Failure);
+        assert_eq!(
+            response.components["database"].details,
+            Some(json!({ "error": "timed out after 1000ms" }))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_report_draining_during_the_pre_stop_delay() {
+        let context = context().await;
+        context.shutdown().trigger();
+
+        let response = readiness(&context, &vec![]).await;
+
+        assert_eq!(response.status, HealthStatus::Draining);
+    }
+}
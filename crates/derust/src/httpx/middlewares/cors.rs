@@ -0,0 +1,298 @@
+use crate::httpx::AppContext;
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::header::{
+    ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+    ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS, ACCESS_CONTROL_MAX_AGE,
+    ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD, ORIGIN, VARY,
+};
+use axum::http::{HeaderMap, HeaderValue, Method, Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+
+const ANY: &str = "*";
+
+/// CORS policy, set through [`AppContext::with_cors`] and overridable per path
+/// prefix with [`AppContext::with_route_cors`]. Loadable through `envx::load_app_config`.
+///
+/// Origins are matched exactly (`https://example.com`), by subdomain
+/// (`https://*.example.com`, which doesn't match `example.com` itself) or
+/// with `*`. Entries without scheme match both `http` and `https`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    /// Headers allowed on preflight. When empty, the requested ones are allowed.
+    pub allowed_headers: Vec<String>,
+    pub exposed_headers: Vec<String>,
+    pub allow_credentials: bool,
+    pub max_age_in_secs: Option<u64>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: vec![],
+            allowed_methods: ["GET", "HEAD", "POST", "PUT", "PATCH", "DELETE"]
+                .iter()
+                .map(|method| method.to_string())
+                .collect(),
+            allowed_headers: vec![],
+            exposed_headers: vec![],
+            allow_credentials: false,
+            max_age_in_secs: None,
+        }
+    }
+}
+
+impl CorsConfig {
+    pub fn is_allowed_origin(&self, origin: &str) -> bool {
+        self.allowed_origins
+            .iter()
+            .any(|allowed_origin| origin_matches(allowed_origin, origin))
+    }
+
+    fn is_allowed_method(&self, method: &str) -> bool {
+        self.allowed_methods
+            .iter()
+            .any(|allowed_method| allowed_method == ANY || allowed_method.eq_ignore_ascii_case(method))
+    }
+
+    fn allow_origin_value(&self, origin: &HeaderValue) -> HeaderValue {
+        // `*` is not accepted by browsers on credentialed requests
+        if !self.allow_credentials && self.allowed_origins.iter().any(|allowed| allowed == ANY) {
+            HeaderValue::from_static(ANY)
+        } else {
+            origin.clone()
+        }
+    }
+
+    fn preflight(&self, origin: &HeaderValue, request_headers: &HeaderMap) -> Response {
+        let requested_method = request_headers
+            .get(ACCESS_CONTROL_REQUEST_METHOD)
+            .and_then(|method| method.to_str().ok())
+            .unwrap_or_default();
+
+        if !self.is_allowed_method(requested_method) {
+            return StatusCode::FORBIDDEN.into_response();
+        }
+
+        let mut response = StatusCode::NO_CONTENT.into_response();
+        let headers = response.headers_mut();
+
+        self.append_common_headers(origin, headers);
+        append_list(headers, ACCESS_CONTROL_ALLOW_METHODS, &self.allowed_methods);
+
+        if self.allowed_headers.is_empty() {
+            if let Some(requested_headers) = request_headers.get(ACCESS_CONTROL_REQUEST_HEADERS) {
+                headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, requested_headers.clone());
+            }
+        } else {
+            append_list(headers, ACCESS_CONTROL_ALLOW_HEADERS, &self.allowed_headers);
+        }
+
+        if let Some(max_age) = self.max_age_in_secs {
+            headers.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from(max_age));
+        }
+
+        response
+    }
+
+    fn append_common_headers(&self, origin: &HeaderValue, headers: &mut HeaderMap) {
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, self.allow_origin_value(origin));
+        headers.append(VARY, HeaderValue::from_static("origin"));
+
+        if self.allow_credentials {
+            headers.insert(ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+        }
+    }
+}
+
+pub async fn cors<S>(
+    State(context): State<AppContext<S>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let config = context.cors_for_path(req.uri().path());
+
+    let Some(origin) = req.headers().get(ORIGIN).cloned() else {
+        return next.run(req).await;
+    };
+
+    if config.allowed_origins.is_empty() {
+        return next.run(req).await;
+    }
+
+    let allowed = origin
+        .to_str()
+        .map(|origin| config.is_allowed_origin(origin))
+        .unwrap_or(false);
+
+    let is_preflight = req.method() == Method::OPTIONS && req.headers().contains_key(ACCESS_CONTROL_REQUEST_METHOD);
+
+    if is_preflight {
+        return if allowed {
+            config.preflight(&origin, req.headers())
+        } else {
+            StatusCode::FORBIDDEN.into_response()
+        };
+    }
+
+    let mut response = next.run(req).await;
+
+    if allowed {
+        let headers = response.headers_mut();
+        config.append_common_headers(&origin, headers);
+        append_list(headers, ACCESS_CONTROL_EXPOSE_HEADERS, &config.exposed_headers);
+    }
+
+    response
+}
+
+fn append_list(headers: &mut HeaderMap, name: axum::http::HeaderName, values: &[String]) {
+    if values.is_empty() {
+        return;
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&values.join(", ")) {
+        headers.insert(name, value);
+    }
+}
+
+fn origin_matches(allowed_origin: &str, origin: &str) -> bool {
+    if allowed_origin == ANY {
+        return true;
+    }
+
+    let allowed_origin = allowed_origin.trim_end_matches('/').to_ascii_lowercase();
+    let origin = origin.trim_end_matches('/').to_ascii_lowercase();
+
+    let (allowed_scheme, allowed_host) = split_scheme(&allowed_origin);
+    let (scheme, host) = split_scheme(&origin);
+
+    if allowed_scheme.is_some() && allowed_scheme != scheme {
+        return false;
+    }
+
+    match allowed_host.strip_prefix('*') {
+        Some(suffix) if suffix.starts_with('.') => host.len() > suffix.len() && host.ends_with(suffix),
+        _ => allowed_host == host,
+    }
+}
+
+fn split_scheme(origin: &str) -> (Option<&str>, &str) {
+    match origin.split_once("://") {
+        Some((scheme, host)) => (Some(scheme), host),
+        None => (None, origin),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_match_origins_exactly_or_by_subdomain() {
+        assert!(origin_matches("https://example.com", "https://example.com"));
+        assert!(origin_matches("example.com", "http://example.com"));
+        assert!(origin_matches("https://*.example.com", "https://api.example.com"));
+        assert!(origin_matches("*", "https://anything.io"));
+
+        assert!(!origin_matches("https://example.com", "https://evil-example.com"));
+        assert!(!origin_matches("https://example.com", "https://example.com.evil.io"));
+        assert!(!origin_matches("https://example.com", "http://example.com"));
+        assert!(!origin_matches("https://*.example.com", "https://example.com"));
+        assert!(!origin_matches("https://*.example.com", "https://evilexample.com"));
+        assert!(!origin_matches("https://example.com", "https://example.com:8443"));
+    }
+}
+
+#[cfg(test)]
+mod middleware_test {
+    use super::*;
+    use crate::httpx::test_context;
+    use axum::routing::get;
+    use axum::{middleware, Router};
+    use tower::ServiceExt;
+
+    #[derive(Clone)]
+    struct AppState;
+
+    async fn router() -> Router {
+        let context = test_context(AppState).await
+            .with_cors(CorsConfig {
+                allowed_origins: vec!["https://*.example.com".to_string()],
+                allow_credentials: true,
+                max_age_in_secs: Some(600),
+                ..CorsConfig::default()
+            })
+            .with_route_cors(
+                "/public",
+                CorsConfig {
+                    allowed_origins: vec!["*".to_string()],
+                    ..CorsConfig::default()
+                },
+            );
+
+        Router::new()
+            .route("/orders", get(|| async { "orders" }))
+            .route("/public/status", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(context.clone(), cors::<AppState>))
+            .with_state(context)
+    }
+
+    fn request(method: Method, uri: &str, origin: &str) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(ORIGIN, origin)
+            .header(ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .header(ACCESS_CONTROL_REQUEST_HEADERS, "content-type")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_answer_preflight_for_allowed_origins() {
+        let response = router()
+            .await
+            .oneshot(request(Method::OPTIONS, "/orders", "https://app.example.com"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "https://app.example.com");
+        assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_HEADERS], "content-type");
+        assert_eq!(response.headers()[ACCESS_CONTROL_MAX_AGE], "600");
+    }
+
+    #[tokio::test]
+    async fn should_reject_preflight_for_unknown_origins() {
+        let response = router()
+            .await
+            .oneshot(request(Method::OPTIONS, "/orders", "https://evil-example.com"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(!response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+
+    #[tokio::test]
+    async fn should_apply_route_overrides() {
+        let response = router()
+            .await
+            .oneshot(request(Method::GET, "/public/status", "https://anything.io"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    }
+}
@@ -1,5 +1,87 @@
-use tower_http::catch_panic::{CatchPanicLayer, DefaultResponseForPanic};
+use crate::httpx::{AppContext, HttpError, ResponseError};
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{Request, Response};
+use axum::middleware::Next;
+use std::any::Any;
+use tower_http::catch_panic::{CatchPanicLayer, DefaultResponseForPanic, ResponseForPanic};
 
-pub fn panic_catcher() -> CatchPanicLayer<DefaultResponseForPanic> {
-    CatchPanicLayer::new()
+/// Marks the responses of caught panics, so they are told apart from handler errors.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PanicCaught;
+
+pub fn panic_catcher() -> CatchPanicLayer<MarkedResponseForPanic> {
+    CatchPanicLayer::custom(MarkedResponseForPanic)
+}
+
+/// The default panic response, marked with [`PanicCaught`].
+#[derive(Debug, Clone, Copy)]
+pub struct MarkedResponseForPanic;
+
+impl ResponseForPanic for MarkedResponseForPanic {
+    type ResponseBody = <DefaultResponseForPanic as ResponseForPanic>::ResponseBody;
+
+    fn response_for_panic(&mut self, err: Box<dyn Any + Send + 'static>) -> Response<Self::ResponseBody> {
+        let mut response = DefaultResponseForPanic::default().response_for_panic(err);
+        response.extensions_mut().insert(PanicCaught);
+        response
+    }
+}
+
+/// Marks the server span with the [`HttpError`](crate::httpx::HttpError) of the
+/// response, along with its cause chain, redacted as the logs are.
+pub async fn error_span<S>(
+    State(context): State<AppContext<S>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response<Body>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let res = next.run(req).await;
+
+    if let Some(ResponseError { error, .. }) = res.extensions().get::<ResponseError>() {
+        tracing::Span::current().record("exception.message", exception_message(&context, error));
+    }
+
+    res
+}
+
+fn exception_message<S>(context: &AppContext<S>, error: &HttpError) -> String
+where
+    S: Clone,
+{
+    let message = std::iter::once(error.to_string())
+        .chain(error.causes())
+        .collect::<Vec<_>>()
+        .join(": ");
+
+    context.redaction().redact_text(&message)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::httpx::context::test_context;
+    use crate::httpx::{HttpTags, RedactionConfig};
+    use axum::http::StatusCode;
+
+    #[tokio::test]
+    async fn should_redact_the_exception_message() {
+        let context = test_context(()).await.with_redaction(RedactionConfig {
+            patterns: vec![regex::Regex::new(r"\d{11}").unwrap()],
+            ..RedactionConfig::default()
+        });
+        let error = HttpError::without_body(
+            StatusCode::CONFLICT,
+            "Customer 12345678901 already exists".to_string(),
+            HttpTags::default(),
+        )
+        .with_source(std::io::Error::other("duplicate key 12345678901"));
+
+        assert_eq!(
+            exception_message(&context, &error),
+            "Customer *** already exists: duplicate key ***"
+        );
+    }
 }
@@ -0,0 +1,167 @@
+use crate::httpx::middlewares::error_handler::PanicCaught;
+use crate::httpx::middlewares::timeout::TimedOut;
+use crate::httpx::AppContext;
+use crate::metricx::{
+    decrement_gauge, increment_gauge, increment_one, record_size, timer, MetricTags,
+};
+use axum::body::Body;
+use axum::extract::{MatchedPath, State};
+use axum::http::{Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+use http_body_util::BodyExt;
+
+/// RED metrics of the HTTP server: duration, in-flight requests, body sizes,
+/// timeouts and panics, tagged with the method and the route template.
+pub async fn http_metrics<S>(
+    State(context): State<AppContext<S>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let metric_tags = route_tags(&context, &req);
+
+    let _in_flight = InFlight::start(&context, metric_tags.clone());
+    let stopwatch = timer::start_stopwatch(&context, "http_server_seconds", metric_tags.clone());
+
+    let (parts, body) = req.into_parts();
+    let mut request_size = BodySize::new(&context, "http_server_request_bytes", metric_tags.clone());
+    let body = body.map_frame(move |frame| {
+        if let Some(data) = frame.data_ref() {
+            request_size.push(data);
+        }
+        frame
+    });
+
+    let res = next.run(Request::from_parts(parts, Body::new(body))).await;
+
+    let status_tags = status_tags(res.status());
+    stopwatch.record(status_tags.clone());
+
+    if res.extensions().get::<TimedOut>().is_some() {
+        increment_one(&context, "http_server_timeouts", metric_tags.clone());
+    }
+    if res.extensions().get::<PanicCaught>().is_some() {
+        increment_one(&context, "http_server_panics", metric_tags.clone());
+    }
+
+    // recorded once the body has been sent, or dropped by a disconnected client
+    let mut response_tags = metric_tags;
+    for tag in status_tags.vec() {
+        response_tags = response_tags.push(tag.key(), tag.value());
+    }
+    let mut response_size = BodySize::new(&context, "http_server_response_bytes", response_tags);
+
+    let (parts, body) = res.into_parts();
+    let body = body.map_frame(move |frame| {
+        if let Some(data) = frame.data_ref() {
+            response_size.push(data);
+        }
+        frame
+    });
+
+    Response::from_parts(parts, Body::new(body))
+}
+
+/// The route template when matched, such as `/orders/{id}`, keeping ids, slugs
+/// and e-mails out of the labels.
+fn route_tags<S>(context: &AppContext<S>, req: &Request<Body>) -> MetricTags
+where
+    S: Clone,
+{
+    match req.extensions().get::<MatchedPath>() {
+        Some(route) => MetricTags::http_server_route(route.as_str(), req.method()),
+        None => MetricTags::http_server_with_patterns(req.uri(), req.method(), context.metric_path_patterns()),
+    }
+}
+
+fn status_tags(status: StatusCode) -> MetricTags {
+    MetricTags::from([
+        ("status", status.as_u16().to_string()),
+        ("status_class", format!("{}xx", status.as_u16() / 100)),
+    ])
+}
+
+struct InFlight<S>
+where
+    S: Clone,
+{
+    context: AppContext<S>,
+    metric_tags: MetricTags,
+}
+
+impl<S> InFlight<S>
+where
+    S: Clone,
+{
+    fn start(context: &AppContext<S>, metric_tags: MetricTags) -> Self {
+        increment_gauge(context, "http_server_in_flight", metric_tags.clone(), 1.0);
+
+        Self {
+            context: context.clone(),
+            metric_tags,
+        }
+    }
+}
+
+impl<S> Drop for InFlight<S>
+where
+    S: Clone,
+{
+    fn drop(&mut self) {
+        decrement_gauge(&self.context, "http_server_in_flight", self.metric_tags.clone(), 1.0);
+    }
+}
+
+struct BodySize<S>
+where
+    S: Clone,
+{
+    context: AppContext<S>,
+    metric_name: &'static str,
+    metric_tags: MetricTags,
+    size: u64,
+}
+
+impl<S> BodySize<S>
+where
+    S: Clone,
+{
+    fn new(context: &AppContext<S>, metric_name: &'static str, metric_tags: MetricTags) -> Self {
+        Self {
+            context: context.clone(),
+            metric_name,
+            metric_tags,
+            size: 0,
+        }
+    }
+
+    fn push(&mut self, data: &[u8]) {
+        self.size += data.len() as u64;
+    }
+}
+
+impl<S> Drop for BodySize<S>
+where
+    S: Clone,
+{
+    fn drop(&mut self) {
+        record_size(&self.context, self.metric_name, self.metric_tags.clone(), self.size);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_tag_the_status_class() {
+        let tags = status_tags(StatusCode::SERVICE_UNAVAILABLE).vec();
+
+        assert_eq!(tags[0].value(), "503");
+        assert_eq!(tags[1].key(), "status_class");
+        assert_eq!(tags[1].value(), "5xx");
+    }
+}
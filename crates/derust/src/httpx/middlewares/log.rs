@@ -1,28 +1,75 @@
 use axum::body::Body;
 use axum::extract::State;
-use axum::http::{HeaderValue, Method, Request, StatusCode, Uri};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{HeaderMap, Method, Request, StatusCode, Uri};
 
-use crate::httpx::{AppContext, HttpTags};
+use crate::httpx::{AppContext, HttpTags, RedactionConfig};
+use crate::tracex::JsonFields;
 use axum::middleware::Next;
-use axum::response::{IntoResponse, Response};
-use bytes::Bytes;
+use axum::response::Response;
 use flate2::read::GzDecoder;
+use http_body_util::BodyExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::io::Read;
+use std::sync::{Arc, Mutex};
 
 use tracing::log::{log_enabled, Level};
 use tracing::{error, info};
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
-use crate::metricx::{timer, MetricTags, Stopwatch};
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
-use regex::Regex;
-use serde_json::Value;
+const DEFAULT_MAX_BODY_BYTES: usize = 16 * 1024;
+
+/// Limits of the request/response logging, set through [`AppContext::with_log_config`]
+/// and loadable through `envx::load_app_config`.
+///
+/// Bodies are only captured in local mode or at debug level, while they are
+/// streamed to the handler and to the client, and only up to the given sizes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    pub max_request_body_bytes: usize,
+    pub max_response_body_bytes: usize,
+    /// Content type prefixes whose bodies are never captured, such as server-sent events.
+    pub streaming_content_types: Vec<String>,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            max_request_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            max_response_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            streaming_content_types: [
+                "text/event-stream",
+                "application/x-ndjson",
+                "application/octet-stream",
+                "application/grpc",
+                "multipart/",
+            ]
+            .iter()
+            .map(|content_type| content_type.to_string())
+            .collect(),
+        }
+    }
+}
+
+impl LogConfig {
+    fn is_streaming(&self, headers: &HeaderMap) -> bool {
+        headers
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| {
+                self.streaming_content_types
+                    .iter()
+                    .any(|streaming| content_type.starts_with(streaming.as_str()))
+            })
+    }
+}
 
 pub async fn local_log_request<S>(
     State(context): State<AppContext<S>>,
     req: Request<Body>,
     next: Next,
-) -> Result<impl IntoResponse, (StatusCode, String)>
+) -> Response
 where
     S: Clone + Send + Sync + 'static,
 {
@@ -33,7 +80,7 @@ pub async fn log_request<S>(
     State(context): State<AppContext<S>>,
     req: Request<Body>,
     next: Next,
-) -> Result<impl IntoResponse, (StatusCode, String)>
+) -> Response
 where
     S: Clone + Send + Sync + 'static,
 {
@@ -45,196 +92,329 @@ async fn log<S>(
     req: Request<Body>,
     next: Next,
     local: bool,
-) -> Result<impl IntoResponse, (StatusCode, String)>
+) -> Response
 where
     S: Clone + Send + Sync + 'static,
 {
-    #[cfg(any(feature = "statsd", feature = "prometheus"))]
-    let stopwatch = start_stopwatch(&context, &req);
+    let config = context.log_config();
+    let method = req.method().clone();
+    let uri = req.uri().clone();
+    let ignored = context.ignore_log_for_paths().contains(&uri.to_string());
+    let with_bodies = !ignored && (local || log_enabled!(Level::Debug));
 
     let (req_parts, req_body) = req.into_parts();
-    let method = req_parts.method.clone();
-    let uri = req_parts.uri.clone();
-
-    let headers = req_parts.headers.clone();
+    let redaction = context.redaction().clone();
+    let request_headers = if with_bodies {
+        redaction.redact_headers(&req_parts.headers)
+    } else {
+        HashMap::new()
+    };
 
-    let option_origin = headers
-        .get("origin")
-        .and_then(|origin| origin.to_str().ok());
+    // the handler reads the body through the capture, which keeps only its first bytes
+    let request_capture = if with_bodies && !config.is_streaming(&req_parts.headers) {
+        Some(Arc::new(Mutex::new(
+            Capture::new(config.max_request_body_bytes).with_content_type(&req_parts.headers),
+        )))
+    } else {
+        None
+    };
 
-    let req_bytes = axum::body::to_bytes(req_body, usize::MAX)
-        .await
-        .map_err(|err| {
-            (
-                StatusCode::BAD_REQUEST,
-                format!("failed to read body: {}", err),
-            )
-        })?;
+    let req_body = match &request_capture {
+        Some(capture) => {
+            let capture = capture.clone();
+            Body::new(req_body.map_frame(move |frame| {
+                if let Some(data) = frame.data_ref() {
+                    if let Ok(mut capture) = capture.lock() {
+                        capture.push(data);
+                    }
+                }
+                frame
+            }))
+        }
+        None => req_body,
+    };
 
-    let mut req = Request::from_parts(req_parts, Body::from(req_bytes.clone()));
+    let mut req = Request::from_parts(req_parts, req_body);
     req.extensions_mut().insert(context.clone());
 
     let res = next.run(req).await;
+
+    if ignored {
+        return res;
+    }
+
     let tags = res
         .extensions()
         .get::<HttpTags>()
         .cloned()
-        .unwrap_or(HttpTags::default());
-
-    let request_body_string = if local || log_enabled!(Level::Debug) {
-        let payload = std::str::from_utf8(&req_bytes)
-            .unwrap_or("Could not convert request bytes into string");
+        .unwrap_or_default();
 
-        let result_value = serde_json::from_str::<Value>(payload);
-        match result_value {
-            Ok(value) => serde_json::to_string(&value).unwrap_or_default(),
-            Err(_) => payload.to_string(),
-        }
-    } else {
-        String::new()
+    let mut entry = LogEntry {
+        method,
+        uri,
+        status: res.status(),
+        tags,
+        request_headers,
+        request: request_capture,
+        response: None,
+        with_bodies,
+        redaction,
     };
 
-    let (mut parts, res_body) = res.into_parts();
-
-    if let Some(origin) = option_origin {
-        let all_origin_enabled = context
-            .allowed_origins()
-            .iter()
-            .any(|allowed_origin| allowed_origin.contains("*"));
+    if !with_bodies || config.is_streaming(res.headers()) {
+        entry.print();
+        return res;
+    }
 
-        if all_origin_enabled
-            || context.allowed_origins().iter().any(|allowed_origin| {
-                normalize_origin(allowed_origin).contains(&normalize_origin(origin))
-            })
-        {
-            if let Ok(header_value) = HeaderValue::from_str(origin) {
-                parts
-                    .headers
-                    .append("Access-Control-Allow-Origin", header_value);
-            }
+    // printed once the response body has been sent, or dropped by a disconnected client
+    entry.response = Some(Capture::new(config.max_response_body_bytes).with_content_type(res.headers()));
+    let (parts, res_body) = res.into_parts();
+    let res_body = res_body.map_frame(move |frame| {
+        if let (Some(data), Some(capture)) = (frame.data_ref(), entry.response.as_mut()) {
+            capture.push(data);
         }
-    }
+        frame
+    });
 
-    let bytes = buffer_and_print(
-        context,
-        method,
-        uri,
-        parts.status,
-        res_body,
-        request_body_string,
-        tags,
-        local,
-    )
-    .await?;
-    let res = Response::from_parts(parts, Body::from(bytes));
-
-    #[cfg(any(feature = "statsd", feature = "prometheus"))]
-    stopwatch.record(MetricTags::from([(
-        "status",
-        res.status().as_u16().to_string(),
-    )]));
-
-    Ok(res)
+    Response::from_parts(parts, Body::new(res_body))
 }
 
-fn normalize_origin(origin: &str) -> String {
-    origin
-        .replace("https://", "")
-        .replace("http://", "")
-        .replace("/", "")
+/// First bytes of a body, along with its total size.
+struct Capture {
+    bytes: Vec<u8>,
+    limit: usize,
+    size: usize,
+    content_type: Option<String>,
 }
 
-async fn buffer_and_print<S>(
-    context: &AppContext<S>,
-    method: Method,
-    uri: Uri,
-    status: StatusCode,
-    res_body: Body,
-    request_body_string: String,
-    tags: HttpTags,
-    local: bool,
-) -> Result<Bytes, (StatusCode, String)>
-where
-    S: Clone,
-{
-    let response_bytes = match axum::body::to_bytes(res_body, usize::MAX).await {
-        Ok(bytes) => bytes,
-        Err(err) => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                format!("failed to read body: {}", err),
-            ));
+impl Capture {
+    fn new(limit: usize) -> Self {
+        Self {
+            bytes: Vec::new(),
+            limit,
+            size: 0,
+            content_type: None,
         }
-    };
+    }
+
+    /// Redacts form bodies by field instead of as text.
+    fn with_content_type(mut self, headers: &HeaderMap) -> Self {
+        self.content_type = headers
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .map(|content_type| content_type.to_string());
+        self
+    }
 
-    if context.ignore_log_for_paths().contains(&uri.to_string()) {
-        return Ok(response_bytes);
+    fn push(&mut self, data: &[u8]) {
+        let remaining = self.limit.saturating_sub(self.bytes.len());
+        self.bytes.extend_from_slice(&data[..remaining.min(data.len())]);
+        self.size += data.len();
     }
 
-    if local || log_enabled!(Level::Debug) {
-        let res_body_str = match decompress_gzip(&response_bytes) {
-            Ok(decompressed) => std::str::from_utf8(&decompressed)
-                .unwrap_or("Could not convert response bytes into string after decompression")
-                .to_string(),
+    fn is_truncated(&self) -> bool {
+        self.size > self.bytes.len()
+    }
+
+    fn request_string(&self, redaction: &RedactionConfig) -> String {
+        let payload = String::from_utf8_lossy(&self.bytes);
+
+        self.with_truncation(redaction.redact_body(&payload, self.content_type.as_deref()))
+    }
+
+    fn response_string(&self, redaction: &RedactionConfig) -> String {
+        let body = match decompress_gzip(&self.bytes) {
+            Ok(decompressed) => {
+                redaction.redact_body(&String::from_utf8_lossy(&decompressed), self.content_type.as_deref())
+            }
             Err(_) => {
-                if is_binary(&response_bytes) {
+                if is_binary(&self.bytes) {
                     "<binary data>".to_string()
                 } else {
-                    std::str::from_utf8(&response_bytes)
-                        .unwrap_or("Could not convert response bytes into string")
-                        .to_string()
+                    redaction.redact_body(&String::from_utf8_lossy(&self.bytes), self.content_type.as_deref())
                 }
             }
         };
 
-        if status.is_server_error() {
+        self.with_truncation(body)
+    }
+
+    fn with_truncation(&self, body: String) -> String {
+        if self.is_truncated() {
+            format!("{body}... <truncated, {} bytes>", self.size)
+        } else {
+            body
+        }
+    }
+}
+
+struct LogEntry {
+    method: Method,
+    uri: Uri,
+    status: StatusCode,
+    tags: HttpTags,
+    request_headers: HashMap<String, String>,
+    request: Option<Arc<Mutex<Capture>>>,
+    response: Option<Capture>,
+    with_bodies: bool,
+    redaction: RedactionConfig,
+}
+
+impl LogEntry {
+    fn print(&self) {
+        let method = &self.method;
+        let uri = &self.uri;
+        let tags = self.redaction.redact_tags(&self.tags);
+        let headers = &self.request_headers;
+        let status = self.status;
+
+        if self.with_bodies {
+            let request_body_string = match &self.request {
+                Some(capture) => capture
+                    .lock()
+                    .map(|capture| capture.request_string(&self.redaction))
+                    .unwrap_or_default(),
+                None => "<streaming>".to_string(),
+            };
+            let res_body_str = match &self.response {
+                Some(capture) => capture.response_string(&self.redaction),
+                None => "<streaming>".to_string(),
+            };
+
+            if status.is_server_error() {
+                error!(
+                    tags = %JsonFields(&tags),
+                    headers = %JsonFields(headers),
+                    "{method} {uri} -> {} :: request={request_body_string} :: response={res_body_str}",
+                    status.as_u16(),
+                );
+            } else {
+                info!(
+                    tags = %JsonFields(&tags),
+                    headers = %JsonFields(headers),
+                    "{method} {uri} -> {} :: request={request_body_string} :: response={res_body_str}",
+                    status.as_u16(),
+                );
+            }
+        } else if status.is_server_error() {
             error!(
-                tags = ?tags.values(),
-                "{method} {uri} -> {} :: request={request_body_string} :: response={res_body_str}",
-                status.as_u16(),
+                tags = %JsonFields(&tags),
+                "{method} {uri} -> {}", status.as_u16(),
             );
         } else {
             info!(
-                tags = ?tags.values(),
-                "{method} {uri} -> {} :: request={request_body_string} :: response={res_body_str}",
-                status.as_u16(),
+                tags = %JsonFields(&tags),
+                "{method} {uri} -> {}", status.as_u16(),
             );
         }
-    } else if status.is_server_error() {
-        error!(
-            tags = ?tags.values(),
-            "{method} {uri} -> {}", status.as_u16(),
-        );
-    } else {
-        info!(
-            tags = ?tags.values(),
-            "{method} {uri} -> {}", status.as_u16(),
-        );
     }
-
-    Ok(response_bytes)
 }
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
-fn start_stopwatch<S>(context: &AppContext<S>, req: &Request<Body>) -> Stopwatch<S>
-where
-    S: Clone,
-{
-    let metric_tags = MetricTags::http_server(req.uri(), req.method());
-    timer::start_stopwatch(&context, "http_server_seconds", metric_tags)
+impl Drop for LogEntry {
+    fn drop(&mut self) {
+        if self.response.is_some() {
+            self.print();
+        }
+    }
 }
 
+/// Decompresses as much as possible, since a truncated capture isn't a complete GZIP stream.
 fn decompress_gzip(data: &[u8]) -> Result<Vec<u8>, &'static str> {
     let mut decoder = GzDecoder::new(data);
     let mut decompressed = Vec::new();
+    let mut buffer = [0u8; 4096];
 
-    match decoder.read_to_end(&mut decompressed) {
-        Ok(_) => Ok(decompressed),
-        Err(_) => Err("Failed to decompress GZIP data"),
+    loop {
+        match decoder.read(&mut buffer) {
+            Ok(0) => return Ok(decompressed),
+            Ok(read) => decompressed.extend_from_slice(&buffer[..read]),
+            Err(_) if !decompressed.is_empty() => return Ok(decompressed),
+            Err(_) => return Err("Failed to decompress GZIP data"),
+        }
     }
 }
 
 fn is_binary(data: &[u8]) -> bool {
     data.iter()
-        .any(|&byte| (byte < 32 || byte > 126) && !matches!(byte, b'\n' | b'\r' | b'\t'))
+        .any(|&byte| !(32..=126).contains(&byte) && !matches!(byte, b'\n' | b'\r' | b'\t'))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_keep_only_the_first_bytes() {
+        let mut capture = Capture::new(5);
+        capture.push(b"{\"na");
+        capture.push(b"me\":\"derust\"}");
+
+        assert_eq!(
+            capture.request_string(&RedactionConfig::default()),
+            "{\"nam... <truncated, 17 bytes>"
+        );
+    }
+
+    #[test]
+    fn should_redact_secrets_cut_off_by_the_truncation() {
+        let mut capture = Capture::new(30);
+        capture.push(b"{\"user\":\"derust\",\"password\":\"s3cr3t-and-more\"}");
+
+        assert_eq!(
+            capture.request_string(&RedactionConfig::default()),
+            "{\"user\":\"derust\",\"password\":\"***\"... <truncated, 46 bytes>"
+        );
+    }
+
+    #[test]
+    fn should_detect_streaming_content_types() {
+        let config = LogConfig::default();
+        let mut headers = HeaderMap::new();
+
+        headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
+        assert!(!config.is_streaming(&headers));
+
+        headers.insert(CONTENT_TYPE, "text/event-stream; charset=utf-8".parse().unwrap());
+        assert!(config.is_streaming(&headers));
+    }
+}
+
+#[cfg(test)]
+mod middleware_test {
+    use super::*;
+    use crate::httpx::test_context;
+    use axum::routing::post;
+    use axum::{middleware, Router};
+    use bytes::Bytes;
+    use tower::ServiceExt;
+
+    #[derive(Clone)]
+    struct AppState;
+
+    #[tokio::test]
+    async fn should_pass_bodies_larger_than_the_limits_through() {
+        let context = test_context(AppState).await
+            .with_log_config(LogConfig {
+                max_request_body_bytes: 10,
+                max_response_body_bytes: 10,
+                ..LogConfig::default()
+            });
+
+        let router = Router::new()
+            .route("/echo", post(|body: Bytes| async move { body }))
+            .layer(middleware::from_fn_with_state(context.clone(), local_log_request::<AppState>))
+            .with_state(context);
+
+        let payload = "a".repeat(100_000);
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/echo")
+            .body(Body::from(payload.clone()))
+            .unwrap();
+
+        let response = router.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        assert_eq!(body, payload);
+    }
 }
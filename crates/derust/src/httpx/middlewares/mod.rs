@@ -1,5 +1,10 @@
 pub mod compression;
+pub mod cors;
 pub mod error_handler;
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
+pub mod http_metrics;
 pub mod log;
+pub mod problem_details;
+pub mod request_id;
 pub mod sensitive_headers;
 pub mod timeout;
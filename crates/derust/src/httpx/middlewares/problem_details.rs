@@ -0,0 +1,82 @@
+use crate::httpx::{AppContext, ResponseError, PROBLEM_JSON_CONTENT_TYPE};
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use axum::http::{HeaderValue, Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+
+/// Renders the responses of every [`HttpError`](crate::httpx::HttpError) as
+/// `application/problem+json`, keeping their status and extra headers.
+pub async fn problem_details<S>(
+    State(context): State<AppContext<S>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let instance = req.uri().path().to_string();
+    let res = next.run(req).await;
+
+    let (Some(config), Some(source)) = (
+        context.problem_details(),
+        res.extensions().get::<ResponseError>().cloned(),
+    ) else {
+        return res;
+    };
+
+    let problem = config.problem(&source.error, &instance, source.trace_id, context.redaction());
+
+    let (mut parts, _) = res.into_parts();
+    parts.status = StatusCode::from_u16(problem.status).unwrap_or(parts.status);
+    parts.headers.remove(CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON_CONTENT_TYPE));
+
+    Response::from_parts(parts, Body::from(serde_json::to_string(&problem).unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::httpx::test_context;
+    use crate::httpx::{HttpError, HttpTags, ProblemDetails, ProblemDetailsConfig};
+    use axum::routing::get;
+    use axum::{middleware, Router};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    async fn not_found() -> Result<String, HttpError> {
+        Err(HttpError::without_body(
+            StatusCode::NOT_FOUND,
+            "Order 42 not found".to_string(),
+            HttpTags::default(),
+        ))
+    }
+
+    #[tokio::test]
+    async fn should_render_http_errors_as_problem_json() {
+        let context = test_context(()).await
+            .with_problem_details(ProblemDetailsConfig::default());
+        let router = Router::new()
+            .route("/orders/{id}", get(not_found))
+            .layer(middleware::from_fn_with_state(context.clone(), problem_details::<()>))
+            .with_state(context);
+
+        let res = router
+            .oneshot(Request::get("/orders/42").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(res.headers()[CONTENT_TYPE], PROBLEM_JSON_CONTENT_TYPE);
+
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let problem: ProblemDetails = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem.title, "Not Found");
+        assert_eq!(problem.detail.as_deref(), Some("Order 42 not found"));
+        assert_eq!(problem.instance.as_deref(), Some("/orders/42"));
+    }
+}
@@ -0,0 +1,114 @@
+use axum::body::Body;
+use axum::http::{HeaderName, HeaderValue, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use std::future::Future;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+pub const REQUEST_ID_TAG: &str = "request_id";
+
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Id of the request being handled, also available through [`current_request_id`].
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Request id of the current request or SQS message. Not inherited by tasks
+/// spawned with `tokio::spawn`.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|request_id| request_id.clone()).ok()
+}
+
+/// Runs `future` with `request_id` as [`current_request_id`].
+pub(crate) async fn with_request_id<F>(request_id: String, future: F) -> F::Output
+where
+    F: Future,
+{
+    REQUEST_ID.scope(request_id, future).await
+}
+
+/// Keeps a well-formed incoming id, so a request can be followed across
+/// services, or generates a UUIDv7.
+pub(crate) fn accept_or_generate(incoming: Option<&str>) -> String {
+    incoming
+        .filter(|request_id| {
+            !request_id.is_empty()
+                && request_id.len() <= MAX_REQUEST_ID_LENGTH
+                && request_id.bytes().all(|byte| byte.is_ascii_graphic())
+        })
+        .map(|request_id| request_id.to_string())
+        .unwrap_or_else(|| Uuid::now_v7().to_string())
+}
+
+pub async fn request_id(mut req: Request<Body>, next: Next) -> Response {
+    let request_id = accept_or_generate(
+        req.headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|request_id| request_id.to_str().ok()),
+    );
+
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let mut response = with_request_id(request_id.clone(), next.run(req)).await;
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::httpx::HttpTags;
+    use axum::routing::get;
+    use axum::{middleware, Router};
+    use tower::ServiceExt;
+
+    fn router() -> Router {
+        Router::new()
+            .route(
+                "/",
+                get(|| async { HttpTags::default().values()[REQUEST_ID_TAG].clone() }),
+            )
+            .layer(middleware::from_fn(request_id))
+    }
+
+    #[tokio::test]
+    async fn should_keep_the_incoming_request_id() {
+        let request = Request::builder()
+            .uri("/")
+            .header(REQUEST_ID_HEADER, "abc-123")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = router().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        assert_eq!(body, "abc-123");
+    }
+
+    #[tokio::test]
+    async fn should_generate_a_request_id() {
+        let request = Request::builder()
+            .uri("/")
+            .header(REQUEST_ID_HEADER, "invalid id")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = router().oneshot(request).await.unwrap();
+        let request_id = response.headers()[REQUEST_ID_HEADER].to_str().unwrap().to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        assert_eq!(Uuid::parse_str(&request_id).unwrap().get_version_num(), 7);
+        assert_eq!(body, request_id);
+    }
+}
@@ -1,19 +1,23 @@
-use axum::http::{header, HeaderName};
-use lazy_static::lazy_static;
+use crate::httpx::RedactionConfig;
+use axum::http::HeaderName;
 use std::sync::Arc;
 use tower_http::sensitive_headers::{
     SetSensitiveRequestHeadersLayer, SetSensitiveResponseHeadersLayer,
 };
 
-lazy_static! {
-    static ref DEFAULT_SENSITIVE_HEADERS: Arc<[HeaderName]> =
-        vec![header::AUTHORIZATION, header::COOKIE].into();
+pub fn request_headers(redaction: &RedactionConfig) -> SetSensitiveRequestHeadersLayer {
+    SetSensitiveRequestHeadersLayer::from_shared(sensitive_headers(redaction))
 }
 
-pub fn request_headers() -> SetSensitiveRequestHeadersLayer {
-    SetSensitiveRequestHeadersLayer::from_shared(DEFAULT_SENSITIVE_HEADERS.clone())
+pub fn response_headers(redaction: &RedactionConfig) -> SetSensitiveResponseHeadersLayer {
+    SetSensitiveResponseHeadersLayer::from_shared(sensitive_headers(redaction))
 }
 
-pub fn response_headers() -> SetSensitiveResponseHeadersLayer {
-    SetSensitiveResponseHeadersLayer::from_shared(DEFAULT_SENSITIVE_HEADERS.clone())
+fn sensitive_headers(redaction: &RedactionConfig) -> Arc<[HeaderName]> {
+    redaction
+        .headers
+        .iter()
+        .filter_map(|header| HeaderName::try_from(header.as_str()).ok())
+        .collect::<Vec<_>>()
+        .into()
 }
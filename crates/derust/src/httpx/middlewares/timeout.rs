@@ -1,13 +1,32 @@
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
 use std::env;
 use std::time::Duration;
-use tower_http::timeout::TimeoutLayer;
 
 const DEFAULT_TIMEOUT_ENV_NAME: &str = "SERVER_TIMEOUT_IN_MILLIS";
 const DEFAULT_TIMEOUT_STR: &str = "10000";
 const DEFAULT_TIMEOUT_U64: u64 = 10000;
 
-pub fn timeouts() -> TimeoutLayer {
+/// Marks the responses of timed out requests, so they are told apart from handler 408s.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TimedOut;
+
+pub fn timeout_duration() -> Duration {
     let timeout = env::var(DEFAULT_TIMEOUT_ENV_NAME).unwrap_or(DEFAULT_TIMEOUT_STR.to_string());
-    let duration = Duration::from_millis(timeout.parse().ok().unwrap_or(DEFAULT_TIMEOUT_U64));
-    TimeoutLayer::new(duration)
+    Duration::from_millis(timeout.parse().ok().unwrap_or(DEFAULT_TIMEOUT_U64))
+}
+
+/// Answers `408 Request Timeout` when the inner layers take longer than the duration.
+pub async fn timeouts(State(duration): State<Duration>, req: Request<Body>, next: Next) -> Response {
+    match tokio::time::timeout(duration, next.run(req)).await {
+        Ok(response) => response,
+        Err(_) => {
+            let mut response = StatusCode::REQUEST_TIMEOUT.into_response();
+            response.extensions_mut().insert(TimedOut);
+            response
+        }
+    }
 }
@@ -1,5 +1,6 @@
 mod auth_extractor;
 mod axum;
+mod bind;
 mod config;
 mod context;
 
@@ -17,14 +18,19 @@ pub mod protect_endpoints_core {
 }
 
 pub(crate) mod extension;
-mod middlewares;
+pub(crate) mod middlewares;
 
 mod health;
+mod redaction;
 
 mod request;
 mod response;
 mod server;
+mod shutdown;
 mod tags;
+#[cfg(feature = "tls")]
+mod tls;
+mod worker;
 
 #[cfg(feature = "prometheus")]
 mod prometheus;
@@ -32,11 +38,33 @@ mod prometheus;
 #[cfg(feature = "growthbook")]
 pub use growthbook_rust_sdk::client::*;
 
+pub use bind::BindAddress;
 pub use config::*;
 pub use context::*;
 pub use error::*;
+pub use problem::*;
+pub use middlewares::cors::CorsConfig;
+pub use middlewares::log::LogConfig;
+pub use middlewares::request_id::{current_request_id, RequestId, REQUEST_ID_HEADER};
+pub use health::{HealthCheck, HealthConfig, HealthIndicator, HealthStatus};
+pub use redaction::{RedactionConfig, REDACTED};
+pub use request::form_request::*;
+pub use request::header_request::*;
 pub use request::json_request::*;
+pub use request::path_request::*;
+pub use request::query_request::*;
+pub use request::validation::*;
+pub use response::bytes::*;
+pub use response::empty::*;
+pub use response::file::*;
 pub use response::json::*;
+pub use response::redirect::*;
+pub use response::sse::*;
 pub use response::*;
 pub use server::*;
+pub use shutdown::{ShutdownConfig, ShutdownHandle};
 pub use tags::*;
+#[cfg(feature = "tls")]
+pub use tls::{PeerIdentity, TlsConfig, TlsConnectInfo, TlsListener};
+pub use tokio_util::sync::CancellationToken;
+pub use worker::BackgroundWorker;
@@ -0,0 +1,279 @@
+use crate::httpx::HttpTags;
+use axum::http::HeaderMap;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+
+pub const REDACTED: &str = "***";
+
+const FORM_CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
+
+/// Masking rules applied to everything derust logs: request/response bodies,
+/// headers, [`HttpTags`] and error messages. Set through [`AppContext::with_redaction`](crate::httpx::AppContext::with_redaction).
+#[derive(Debug, Clone)]
+pub struct RedactionConfig {
+    /// JSON keys masked wherever they appear, case-insensitive.
+    pub keys: Vec<String>,
+    /// Dot separated JSON paths from the root, such as `customer.document`.
+    /// Arrays are traversed, so `items.card` masks the `card` of every item.
+    pub paths: Vec<String>,
+    /// Every match is masked, in bodies and in any other logged text.
+    pub patterns: Vec<Regex>,
+    /// Headers masked in logs, also marked as sensitive for the tracing layers.
+    pub headers: Vec<String>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            keys: [
+                "password",
+                "secret",
+                "token",
+                "access_token",
+                "refresh_token",
+                "client_secret",
+                "authorization",
+                "cpf",
+                "card_number",
+                "cvv",
+            ]
+            .iter()
+            .map(|key| key.to_string())
+            .collect(),
+            paths: vec![],
+            patterns: vec![],
+            headers: ["authorization", "proxy-authorization", "cookie", "set-cookie", "x-api-key"]
+                .iter()
+                .map(|header| header.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl RedactionConfig {
+    pub fn redact_json(&self, value: &mut Value) {
+        let paths = self
+            .paths
+            .iter()
+            .map(|path| path.split('.').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        self.redact_value(value, &paths.iter().map(|path| path.as_slice()).collect::<Vec<_>>());
+    }
+
+    /// Masks JSON payloads by key and path, then applies the patterns. Text
+    /// that isn't valid JSON, such as a truncated body, is masked by key only.
+    pub fn redact_text(&self, text: &str) -> String {
+        let text = match serde_json::from_str::<Value>(text) {
+            Ok(mut value) if value.is_object() || value.is_array() => {
+                self.redact_json(&mut value);
+                serde_json::to_string(&value).unwrap_or_default()
+            }
+            _ => self.redact_json_keys(text),
+        };
+
+        self.redact_patterns(&text)
+    }
+
+    /// Same as [`RedactionConfig::redact_text`], masking form fields by key
+    /// when the content type is `application/x-www-form-urlencoded`.
+    pub fn redact_body(&self, body: &str, content_type: Option<&str>) -> String {
+        if content_type.is_some_and(|content_type| content_type.starts_with(FORM_CONTENT_TYPE)) {
+            self.redact_patterns(&self.redact_form(body))
+        } else {
+            self.redact_text(body)
+        }
+    }
+
+    pub fn redact_header(&self, name: &str, value: &str) -> String {
+        if self.is_sensitive_header(name) {
+            REDACTED.to_string()
+        } else {
+            self.redact_patterns(value)
+        }
+    }
+
+    pub fn redact_headers(&self, headers: &HeaderMap) -> HashMap<String, String> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = value.to_str().unwrap_or("<binary data>");
+                (name.to_string(), self.redact_header(name.as_str(), value))
+            })
+            .collect()
+    }
+
+    pub fn redact_tags(&self, tags: &HttpTags) -> HashMap<String, String> {
+        tags.values()
+            .into_iter()
+            .map(|(key, value)| {
+                let value = if self.is_sensitive_key(&key) {
+                    REDACTED.to_string()
+                } else {
+                    self.redact_patterns(&value)
+                };
+                (key, value)
+            })
+            .collect()
+    }
+
+    pub fn is_sensitive_header(&self, name: &str) -> bool {
+        self.headers.iter().any(|header| header.eq_ignore_ascii_case(name))
+    }
+
+    fn is_sensitive_key(&self, key: &str) -> bool {
+        self.keys.iter().any(|sensitive| sensitive.eq_ignore_ascii_case(key))
+    }
+
+    /// Masks the values of the sensitive keys found in text, even when the
+    /// string value is cut off before its closing quote.
+    fn redact_json_keys(&self, text: &str) -> String {
+        if self.keys.is_empty() {
+            return text.to_string();
+        }
+
+        let keys = self.keys.iter().map(|key| regex::escape(key)).collect::<Vec<_>>().join("|");
+        let pattern = format!(r#"(?i)("(?:{keys})"\s*:\s*)(?:"(?:[^"\\]|\\.)*"?|[^\s,}}\]]+)"#);
+
+        match Regex::new(&pattern) {
+            Ok(pattern) => pattern.replace_all(text, format!("${{1}}\"{REDACTED}\"")).to_string(),
+            Err(_) => text.to_string(),
+        }
+    }
+
+    fn redact_form(&self, body: &str) -> String {
+        let fields = match serde_urlencoded::from_str::<Vec<(String, String)>>(body) {
+            Ok(fields) => fields,
+            Err(_) => return body.to_string(),
+        };
+
+        let fields = fields
+            .into_iter()
+            .map(|(key, value)| {
+                if self.is_sensitive_key(&key) {
+                    (key, REDACTED.to_string())
+                } else {
+                    (key, value)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        serde_urlencoded::to_string(&fields).unwrap_or_default()
+    }
+
+    fn redact_patterns(&self, text: &str) -> String {
+        self.patterns
+            .iter()
+            .fold(text.to_string(), |text, pattern| pattern.replace_all(&text, REDACTED).to_string())
+    }
+
+    fn redact_value(&self, value: &mut Value, paths: &[&[&str]]) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    let matches_path = paths.iter().any(|path| path.len() == 1 && path[0] == key);
+
+                    if matches_path || self.is_sensitive_key(key) {
+                        *value = Value::String(REDACTED.to_string());
+                        continue;
+                    }
+
+                    let nested_paths = paths
+                        .iter()
+                        .filter(|path| path.len() > 1 && path[0] == key)
+                        .map(|path| &path[1..])
+                        .collect::<Vec<_>>();
+
+                    self.redact_value(value, &nested_paths);
+                }
+            }
+            Value::Array(values) => {
+                for value in values {
+                    self.redact_value(value, paths);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn config() -> RedactionConfig {
+        RedactionConfig {
+            paths: vec!["customer.document".to_string(), "items.card".to_string()],
+            patterns: vec![Regex::new(r"\b\d{4}-\d{4}-\d{4}-\d{4}\b").unwrap()],
+            ..RedactionConfig::default()
+        }
+    }
+
+    #[test]
+    fn should_redact_json_by_key_and_path() {
+        let body = json!({
+            "password": "123456",
+            "customer": { "name": "John", "document": "12345678900", "Token": "abc" },
+            "items": [{ "card": "visa", "quantity": 1 }],
+            "document": "kept",
+        });
+
+        let redacted: Value = serde_json::from_str(&config().redact_text(&body.to_string())).unwrap();
+
+        assert_eq!(
+            redacted,
+            json!({
+                "password": "***",
+                "customer": { "name": "John", "document": "***", "Token": "***" },
+                "items": [{ "card": "***", "quantity": 1 }],
+                "document": "kept",
+            })
+        );
+    }
+
+    #[test]
+    fn should_redact_patterns_in_text() {
+        assert_eq!(
+            config().redact_text("Failed to charge card 4111-1111-1111-1111"),
+            "Failed to charge card ***"
+        );
+    }
+
+    #[test]
+    fn should_redact_keys_of_truncated_json() {
+        let truncated = r#"{"customer":{"name":"John","token":"abc123","password":"s3cr"#;
+
+        assert_eq!(
+            config().redact_text(truncated),
+            r#"{"customer":{"name":"John","token":"***","password":"***""#
+        );
+        assert_eq!(
+            config().redact_text(r#"{"cvv": 123, "card_number" : "4111"#),
+            r#"{"cvv": "***", "card_number" : "***""#
+        );
+    }
+
+    #[test]
+    fn should_redact_form_fields_by_key() {
+        let body = "username=john&password=s3cr%26t&client_secret=abc";
+
+        assert_eq!(
+            config().redact_body(body, Some("application/x-www-form-urlencoded; charset=utf-8")),
+            "username=john&password=***&client_secret=***"
+        );
+        assert_eq!(config().redact_body(body, Some("text/plain")), body);
+    }
+
+    #[test]
+    fn should_redact_headers_and_tags() {
+        let config = config();
+        let tags = HttpTags::from([("cpf", "12345678900"), ("error", "card 4111-1111-1111-1111")]);
+
+        assert_eq!(config.redact_header("Authorization", "Bearer abc"), "***");
+        assert_eq!(config.redact_header("Accept", "application/json"), "application/json");
+        assert_eq!(config.redact_tags(&tags)["cpf"], "***");
+        assert_eq!(config.redact_tags(&tags)["error"], "card ***");
+    }
+}
@@ -0,0 +1,31 @@
+use crate::httpx::request::bad_request;
+use crate::httpx::request::validation::RequestValue;
+use crate::httpx::HttpError;
+use axum::extract::{rejection::FormRejection, FromRequest, Request};
+
+/// `application/x-www-form-urlencoded` body, or the query of `GET` and `HEAD`
+/// requests, rejected with `400 {"message"}`.
+pub struct FormRequest<T>(pub T);
+
+impl<S, T> FromRequest<S> for FormRequest<T>
+where
+    axum::Form<T>: FromRequest<S, Rejection = FormRejection>,
+    S: Send + Sync,
+{
+    type Rejection = HttpError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match axum::Form::<T>::from_request(req, state).await {
+            Ok(value) => Ok(Self(value.0)),
+            Err(rejection) => Err(bad_request("form", rejection.body_text(), rejection)),
+        }
+    }
+}
+
+impl<T> RequestValue for FormRequest<T> {
+    type Value = T;
+
+    fn value(&self) -> &T {
+        &self.0
+    }
+}
@@ -0,0 +1,88 @@
+use crate::httpx::request::bad_request;
+use crate::httpx::request::validation::RequestValue;
+use crate::httpx::HttpError;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use serde::de::DeserializeOwned;
+
+/// Request headers deserialized by their lowercase names, such as
+/// `#[serde(rename = "x-tenant-id")]`, rejected with `400 {"message"}`.
+/// Only the first value of a repeated header is read.
+pub struct HeaderRequest<T>(pub T);
+
+impl<S, T> FromRequestParts<S> for HeaderRequest<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = HttpError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let headers = parts
+            .headers
+            .keys()
+            .filter_map(|name| {
+                let value = parts.headers.get(name)?.to_str().ok()?;
+                Some((name.as_str(), value))
+            })
+            .collect::<Vec<_>>();
+
+        let encoded = serde_urlencoded::to_string(headers)
+            .map_err(|error| bad_request("headers", format!("Failed to read headers: {error}"), error))?;
+
+        serde_urlencoded::from_str::<T>(&encoded)
+            .map(Self)
+            .map_err(|error| bad_request("headers", format!("Failed to deserialize headers: {error}"), error))
+    }
+}
+
+impl<T> RequestValue for HeaderRequest<T> {
+    type Value = T;
+
+    fn value(&self) -> &T {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::httpx::HttpResponse;
+    use axum::http::{Request, StatusCode};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct TenantHeaders {
+        #[serde(rename = "x-tenant-id")]
+        tenant_id: u64,
+        #[serde(rename = "x-locale")]
+        locale: Option<String>,
+    }
+
+    async fn extract(request: Request<()>) -> Result<TenantHeaders, HttpError> {
+        let (mut parts, _) = request.into_parts();
+        HeaderRequest::<TenantHeaders>::from_request_parts(&mut parts, &())
+            .await
+            .map(|HeaderRequest(headers)| headers)
+    }
+
+    #[tokio::test]
+    async fn should_deserialize_headers() {
+        let headers = extract(Request::get("/").header("X-Tenant-Id", "42").body(()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(headers.tenant_id, 42);
+        assert_eq!(headers.locale, None);
+
+        let error = extract(Request::get("/").header("x-tenant-id", "acme").body(()).unwrap())
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+        assert!(error.response_json().unwrap()["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Failed to deserialize headers"));
+    }
+}
@@ -1,15 +1,9 @@
-use crate::httpx::{HttpError, HttpTags};
-use axum::{
-    extract::{rejection::JsonRejection, FromRequest, Request},
-    http::StatusCode,
-    response::IntoResponse,
-};
-use serde_json::{json, Value};
-
-pub async fn handler(JsonRequest(value): JsonRequest<Value>) -> impl IntoResponse {
-    JsonRequest(dbg!(value));
-}
+use crate::httpx::request::bad_request;
+use crate::httpx::request::validation::RequestValue;
+use crate::httpx::HttpError;
+use axum::extract::{rejection::JsonRejection, FromRequest, Request};
 
+/// JSON body, rejected with `400 {"message"}`.
 pub struct JsonRequest<T>(pub T);
 
 impl<S, T> FromRequest<S> for JsonRequest<T>
@@ -20,21 +14,17 @@ where
     type Rejection = HttpError;
 
     async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
-        let (parts, body) = req.into_parts();
-
-        let req = Request::from_parts(parts, body);
-
         match axum::Json::<T>::from_request(req, state).await {
             Ok(value) => Ok(Self(value.0)),
-            Err(rejection) => Err(HttpError::with_body(
-                StatusCode::BAD_REQUEST,
-                format!("Invalid JSON: {:?}", rejection),
-                json!({
-                    "message": rejection.body_text(),
-                })
-                .to_string(),
-                HttpTags::default(),
-            )),
+            Err(rejection) => Err(bad_request("JSON", rejection.body_text(), rejection)),
         }
     }
 }
+
+impl<T> RequestValue for JsonRequest<T> {
+    type Value = T;
+
+    fn value(&self) -> &T {
+        &self.0
+    }
+}
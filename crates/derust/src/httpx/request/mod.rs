@@ -1 +1,26 @@
-pub mod json_request;
\ No newline at end of file
+use crate::httpx::{HttpError, HttpTags};
+use axum::http::StatusCode;
+use serde_json::json;
+
+pub mod form_request;
+pub mod header_request;
+pub mod json_request;
+pub mod path_request;
+pub mod query_request;
+pub mod validation;
+
+/// `400` with the `{"message"}` body shared by the request extractors.
+pub(crate) fn bad_request<E>(kind: &str, message: String, rejection: E) -> HttpError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    HttpError::with_json(
+        StatusCode::BAD_REQUEST,
+        format!("Invalid {kind}: {rejection:?}"),
+        json!({
+            "message": message,
+        }),
+        HttpTags::default(),
+    )
+    .with_source(rejection)
+}
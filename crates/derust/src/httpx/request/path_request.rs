@@ -0,0 +1,31 @@
+use crate::httpx::request::bad_request;
+use crate::httpx::request::validation::RequestValue;
+use crate::httpx::HttpError;
+use axum::extract::{rejection::PathRejection, FromRequestParts, Path};
+use axum::http::request::Parts;
+
+/// Path parameters of the matched route, rejected with `400 {"message"}`.
+pub struct PathRequest<T>(pub T);
+
+impl<S, T> FromRequestParts<S> for PathRequest<T>
+where
+    Path<T>: FromRequestParts<S, Rejection = PathRejection>,
+    S: Send + Sync,
+{
+    type Rejection = HttpError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match Path::<T>::from_request_parts(parts, state).await {
+            Ok(value) => Ok(Self(value.0)),
+            Err(rejection) => Err(bad_request("path", rejection.body_text(), rejection)),
+        }
+    }
+}
+
+impl<T> RequestValue for PathRequest<T> {
+    type Value = T;
+
+    fn value(&self) -> &T {
+        &self.0
+    }
+}
@@ -0,0 +1,31 @@
+use crate::httpx::request::bad_request;
+use crate::httpx::request::validation::RequestValue;
+use crate::httpx::HttpError;
+use axum::extract::{rejection::QueryRejection, FromRequestParts, Query};
+use axum::http::request::Parts;
+
+/// Query string, rejected with `400 {"message"}`.
+pub struct QueryRequest<T>(pub T);
+
+impl<S, T> FromRequestParts<S> for QueryRequest<T>
+where
+    Query<T>: FromRequestParts<S, Rejection = QueryRejection>,
+    S: Send + Sync,
+{
+    type Rejection = HttpError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match Query::<T>::from_request_parts(parts, state).await {
+            Ok(value) => Ok(Self(value.0)),
+            Err(rejection) => Err(bad_request("query", rejection.body_text(), rejection)),
+        }
+    }
+}
+
+impl<T> RequestValue for QueryRequest<T> {
+    type Value = T;
+
+    fn value(&self) -> &T {
+        &self.0
+    }
+}
@@ -0,0 +1,225 @@
+use crate::httpx::{HttpError, HttpTags};
+use axum::extract::{FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fmt::{Display, Formatter};
+
+/// Checks of a request value, run by [`Validated`] once it's extracted.
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Field errors of a request, answered as `422 {"message", "errors": [{"field", "message"}]}`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationErrors(Vec<FieldError>);
+
+impl ValidationErrors {
+    pub fn add(&mut self, field: &str, message: &str) {
+        self.0.push(FieldError {
+            field: field.to_string(),
+            message: message.to_string(),
+        });
+    }
+
+    /// Adds the error when `valid` is false.
+    pub fn check(&mut self, valid: bool, field: &str, message: &str) -> &mut Self {
+        if !valid {
+            self.add(field, message);
+        }
+        self
+    }
+
+    pub fn errors(&self) -> &[FieldError] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl Display for ValidationErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let errors = self
+            .0
+            .iter()
+            .map(|error| format!("{} {}", error.field, error.message))
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", errors.join(", "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+impl From<ValidationErrors> for HttpError {
+    fn from(errors: ValidationErrors) -> Self {
+        HttpError::with_json(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Invalid request: {errors}"),
+            json!({
+                "message": "Invalid request",
+                "errors": errors.errors(),
+            }),
+            HttpTags::default(),
+        )
+        .with_source(errors)
+    }
+}
+
+/// The value of a derust request extractor, such as the `T` of `JsonRequest<T>`.
+pub trait RequestValue {
+    type Value;
+
+    fn value(&self) -> &Self::Value;
+}
+
+/// Runs [`Validate`] on the value of the inner extractor:
+/// `Validated(JsonRequest(order)): Validated<JsonRequest<Order>>`.
+pub struct Validated<E>(pub E);
+
+impl<S, E> FromRequest<S> for Validated<E>
+where
+    E: FromRequest<S, Rejection = HttpError> + RequestValue,
+    E::Value: Validate,
+    S: Send + Sync,
+{
+    type Rejection = HttpError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let extracted = E::from_request(req, state).await?;
+        extracted.value().validate()?;
+
+        Ok(Self(extracted))
+    }
+}
+
+impl<S, E> FromRequestParts<S> for Validated<E>
+where
+    E: FromRequestParts<S, Rejection = HttpError> + RequestValue,
+    E::Value: Validate,
+    S: Send + Sync,
+{
+    type Rejection = HttpError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let extracted = E::from_request_parts(parts, state).await?;
+        extracted.value().validate()?;
+
+        Ok(Self(extracted))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::httpx::{JsonRequest, QueryRequest};
+    use axum::body::Body;
+    use axum::routing::{get, post};
+    use axum::Router;
+    use http_body_util::BodyExt;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    #[derive(Deserialize)]
+    struct Order {
+        quantity: i32,
+        email: String,
+    }
+
+    impl Validate for Order {
+        fn validate(&self) -> Result<(), ValidationErrors> {
+            let mut errors = ValidationErrors::default();
+            errors
+                .check(self.quantity > 0, "quantity", "must be positive")
+                .check(self.email.contains('@'), "email", "must be an e-mail");
+            errors.into_result()
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Page {
+        size: u32,
+    }
+
+    impl Validate for Page {
+        fn validate(&self) -> Result<(), ValidationErrors> {
+            let mut errors = ValidationErrors::default();
+            errors.check(self.size <= 100, "size", "must be at most 100");
+            errors.into_result()
+        }
+    }
+
+    fn router() -> Router {
+        Router::new()
+            .route(
+                "/orders",
+                post(|Validated(JsonRequest(order)): Validated<JsonRequest<Order>>| async move {
+                    order.quantity.to_string()
+                }),
+            )
+            .route(
+                "/orders",
+                get(|Validated(QueryRequest(page)): Validated<QueryRequest<Page>>| async move {
+                    page.size.to_string()
+                }),
+            )
+    }
+
+    async fn send(request: axum::http::Request<Body>) -> (StatusCode, String) {
+        let res = router().oneshot(request).await.unwrap();
+        let status = res.status();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+
+        (status, String::from_utf8_lossy(&body).to_string())
+    }
+
+    #[tokio::test]
+    async fn should_answer_field_errors_with_422() {
+        let (status, body) = send(
+            axum::http::Request::post("/orders")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"quantity": 0, "email": "john"}"#))
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap(),
+            json!({
+                "message": "Invalid request",
+                "errors": [
+                    { "field": "quantity", "message": "must be positive" },
+                    { "field": "email", "message": "must be an e-mail" },
+                ],
+            })
+        );
+
+        let (status, body) = send(axum::http::Request::get("/orders?size=10").body(Body::empty()).unwrap()).await;
+        assert_eq!((status, body.as_str()), (StatusCode::OK, "10"));
+
+        let (status, _) = send(axum::http::Request::get("/orders?size=500").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, body) = send(axum::http::Request::get("/orders?size=ten").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("message"));
+    }
+}
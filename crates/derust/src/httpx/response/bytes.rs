@@ -0,0 +1,87 @@
+use crate::httpx::response::{headers_with_default, tags_with_trace_id, take_once};
+use crate::httpx::{HttpResponse, HttpTags};
+use axum::body::Body;
+use axum::http::StatusCode;
+use axum::BoxError;
+use bytes::Bytes;
+use futures_util::TryStream;
+use std::sync::Mutex;
+
+const OCTET_STREAM: &str = "application/octet-stream";
+
+/// Binary body, in memory or streamed, such as images or generated reports.
+pub struct BytesResponse {
+    status_code: StatusCode,
+    // behind a mutex, since bodies aren't `Sync`
+    body: Mutex<Option<Body>>,
+    content_type: String,
+    response_headers: Option<Vec<(String, String)>>,
+    tags: HttpTags,
+}
+
+impl BytesResponse {
+    pub fn new(status_code: StatusCode, response_body: impl Into<Bytes>, tags: HttpTags) -> Self {
+        Self::with_body(status_code, Body::from(response_body.into()), tags)
+    }
+
+    /// Sends the chunks as they are produced. An error ends the response abruptly.
+    pub fn from_stream<S>(status_code: StatusCode, stream: S, tags: HttpTags) -> Self
+    where
+        S: TryStream + Send + 'static,
+        S::Ok: Into<Bytes>,
+        S::Error: Into<BoxError>,
+    {
+        Self::with_body(status_code, Body::from_stream(stream), tags)
+    }
+
+    fn with_body(status_code: StatusCode, body: Body, tags: HttpTags) -> Self {
+        Self {
+            status_code,
+            body: Mutex::new(Some(body)),
+            content_type: OCTET_STREAM.to_string(),
+            response_headers: None,
+            tags,
+        }
+    }
+
+    /// `application/octet-stream` by default.
+    pub fn with_content_type(mut self, content_type: &str) -> Self {
+        self.content_type = content_type.to_string();
+        self
+    }
+
+    pub fn with_headers(mut self, response_headers: Vec<(String, String)>) -> Self {
+        self.response_headers = Some(response_headers);
+        self
+    }
+}
+
+impl HttpResponse for BytesResponse {
+    fn status_code(&self) -> StatusCode {
+        self.status_code
+    }
+
+    fn error_message(&self) -> Option<String> {
+        None
+    }
+
+    fn response_body(&self) -> Option<String> {
+        None
+    }
+
+    fn response_headers(&self) -> Option<Vec<(String, String)>> {
+        Some(headers_with_default(
+            &self.response_headers,
+            "Content-Type",
+            &self.content_type,
+        ))
+    }
+
+    fn tags(&self) -> HttpTags {
+        tags_with_trace_id(&self.tags)
+    }
+
+    fn take_body(&mut self) -> Body {
+        take_once(&mut self.body)
+    }
+}
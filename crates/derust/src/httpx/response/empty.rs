@@ -0,0 +1,48 @@
+use crate::httpx::response::tags_with_trace_id;
+use crate::httpx::{HttpResponse, HttpTags};
+use axum::http::StatusCode;
+
+/// Response without body, such as `204 No Content` or `202 Accepted`.
+#[derive(Clone)]
+pub struct EmptyResponse {
+    status_code: StatusCode,
+    response_headers: Option<Vec<(String, String)>>,
+    tags: HttpTags,
+}
+
+impl EmptyResponse {
+    pub fn new(status_code: StatusCode, tags: HttpTags) -> Self {
+        Self {
+            status_code,
+            response_headers: None,
+            tags,
+        }
+    }
+
+    pub fn with_headers(mut self, response_headers: Vec<(String, String)>) -> Self {
+        self.response_headers = Some(response_headers);
+        self
+    }
+}
+
+impl HttpResponse for EmptyResponse {
+    fn status_code(&self) -> StatusCode {
+        self.status_code
+    }
+
+    fn error_message(&self) -> Option<String> {
+        None
+    }
+
+    fn response_body(&self) -> Option<String> {
+        None
+    }
+
+    fn response_headers(&self) -> Option<Vec<(String, String)>> {
+        self.response_headers.clone()
+    }
+
+    fn tags(&self) -> HttpTags {
+        tags_with_trace_id(&self.tags)
+    }
+}
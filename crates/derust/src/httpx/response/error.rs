@@ -1,9 +1,11 @@
+use crate::httpx::response::tags_with_trace_id;
 use crate::httpx::tags::HttpTags;
 use crate::httpx::HttpResponse;
 use axum::body::Body;
-use axum::http::{Response, StatusCode};
-use axum_tracing_opentelemetry::tracing_opentelemetry_instrumentation_sdk::find_current_trace_id;
+use axum::http::{Extensions, Response, StatusCode};
+use axum::response::IntoResponse;
 use serde_json::Value;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct HttpError {
@@ -12,6 +14,19 @@ pub struct HttpError {
     response_body: Box<Option<String>>,
     response_headers: Box<Option<Vec<(String, String)>>>,
     tags: HttpTags,
+    source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    extensions: Extensions,
+}
+
+/// Kept among the extensions, so the error stays small.
+#[derive(Clone)]
+struct ErrorCode(String);
+
+/// The [`HttpError`] behind a response, for the layers rendering or reporting it.
+#[derive(Clone)]
+pub(crate) struct ResponseError {
+    pub(crate) error: Arc<HttpError>,
+    pub(crate) trace_id: Option<String>,
 }
 
 impl std::fmt::Display for HttpError {
@@ -20,7 +35,13 @@ impl std::fmt::Display for HttpError {
     }
 }
 
-impl std::error::Error for HttpError {}
+impl std::error::Error for HttpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn std::error::Error + 'static))
+    }
+}
 
 impl HttpError {
     pub fn with_body(
@@ -35,6 +56,8 @@ impl HttpError {
             response_body: Box::new(Some(response_body)),
             response_headers: Box::new(None),
             tags,
+            source: None,
+            extensions: Extensions::new(),
         }
     }
 
@@ -52,6 +75,8 @@ impl HttpError {
             response_body: Box::new(Some(response_body.to_string())),
             response_headers: Box::new(Some(headers)),
             tags,
+            source: None,
+            extensions: Extensions::new(),
         }
     }
 
@@ -62,12 +87,20 @@ impl HttpError {
             response_body: Box::new(None),
             response_headers: Box::new(None),
             tags,
+            source: None,
+            extensions: Extensions::new(),
         }
     }
 
     pub fn with_headers(mut self, response_headers: Vec<(String, String)>) -> Self {
         let mut headers: Vec<(String, String)> = self.response_headers.unwrap_or_default();
 
+        // the given headers replace the ones already set under the same name
+        headers.retain(|(key, _)| {
+            !response_headers
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case(key))
+        });
         for (key, value) in response_headers {
             headers.push((key, value));
         }
@@ -77,6 +110,65 @@ impl HttpError {
         self
     }
 
+    /// Keeps the error that caused this one, such as a `DatabaseError`.
+    pub fn with_source<E>(mut self, source: E) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    /// The source of this error, when it's a `T`.
+    pub fn source_as<T>(&self) -> Option<&T>
+    where
+        T: std::error::Error + 'static,
+    {
+        self.source.as_deref()?.downcast_ref::<T>()
+    }
+
+    /// Messages of the sources, from the closest to the root cause.
+    pub fn causes(&self) -> Vec<String> {
+        let mut causes = vec![];
+        let mut source = std::error::Error::source(self);
+
+        while let Some(error) = source {
+            causes.push(error.to_string());
+            source = error.source();
+        }
+
+        causes
+    }
+
+    /// Stable identifier of the error, such as `order_already_paid`, logged and
+    /// added to the problem details.
+    pub fn with_code(mut self, code: &str) -> Self {
+        self.extensions.insert(ErrorCode(code.to_string()));
+        self
+    }
+
+    pub fn code(&self) -> Option<&str> {
+        self.extensions
+            .get::<ErrorCode>()
+            .map(|ErrorCode(code)| code.as_str())
+    }
+
+    /// Attaches any typed value, such as a domain error, read back with [`HttpError::extension`].
+    pub fn with_extension<T>(mut self, value: T) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.extensions.insert(value);
+        self
+    }
+
+    pub fn extension<T>(&self) -> Option<&T>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.extensions.get::<T>()
+    }
+
     pub fn response_json(&self) -> Option<Value> {
         if let Some(body) = self.response_body() {
             return serde_json::from_str(&body).ok();
@@ -96,51 +188,87 @@ impl HttpResponse for HttpError {
     }
 
     fn response_body(&self) -> Option<String> {
-        self.response_body.clone().map(|s| Some(s)).unwrap_or(None)
+        *self.response_body.clone()
     }
 
     fn response_headers(&self) -> Option<Vec<(String, String)>> {
-        self.response_headers
-            .clone()
-            .map(|v| Some(v))
-            .unwrap_or(None)
+        *self.response_headers.clone()
     }
 
     fn tags(&self) -> HttpTags {
         let mut tags = self.tags.clone();
         tags.add("error_message", &self.error_message);
 
-        if !tags
-            .values()
-            .iter()
-            .any(|(key, _)| key.to_uppercase() == "X-TRACE-ID".to_uppercase())
-        {
-            if let Some(trace_id) = find_current_trace_id() {
-                tags.add("x-trace-id", &trace_id);
-            }
+        if let Some(code) = self.code() {
+            tags.add("error_code", code);
+        }
+
+        let causes = self.causes();
+        if !causes.is_empty() {
+            tags.add("error_causes", &causes.join(": "));
         }
 
-        tags
+        tags_with_trace_id(&tags)
     }
 }
 
 impl From<HttpError> for Response<Body> {
     fn from(http_error: HttpError) -> Self {
-        let mut response = Response::builder().status(http_error.status_code());
+        http_error.into_response()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
 
-        if let Some(headers) = http_error.response_headers() {
-            for (key, value) in headers {
-                response = response.header(&key, value);
-            }
+    #[derive(Debug)]
+    struct ConnectionRefused;
+
+    impl std::fmt::Display for ConnectionRefused {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "connection refused")
         }
+    }
+
+    impl std::error::Error for ConnectionRefused {}
+
+    #[derive(Debug)]
+    struct PaymentFailed(ConnectionRefused);
+
+    impl std::fmt::Display for PaymentFailed {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "payment failed")
+        }
+    }
+
+    impl std::error::Error for PaymentFailed {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct OrderId(u64);
+
+    #[test]
+    fn should_keep_the_source_code_and_extensions() {
+        let error = HttpError::without_body(
+            StatusCode::BAD_GATEWAY,
+            "Failed to pay order".to_string(),
+            HttpTags::default(),
+        )
+        .with_source(PaymentFailed(ConnectionRefused))
+        .with_code("payment_failed")
+        .with_extension(OrderId(42));
 
-        let body = http_error.response_body().unwrap_or_default();
+        assert!(error.source_as::<PaymentFailed>().is_some());
+        assert!(error.source_as::<ConnectionRefused>().is_none());
+        assert_eq!(error.causes(), vec!["payment failed", "connection refused"]);
+        assert_eq!(error.extension::<OrderId>(), Some(&OrderId(42)));
 
-        response.body(Body::from(body)).unwrap_or_else(|_| {
-            Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(Body::empty())
-                .unwrap()
-        })
+        let tags = error.tags().values();
+        assert_eq!(tags["error_code"], "payment_failed");
+        assert_eq!(tags["error_causes"], "payment failed: connection refused");
     }
 }
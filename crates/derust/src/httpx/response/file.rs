@@ -0,0 +1,258 @@
+use crate::httpx::response::{headers_with_default, tags_with_trace_id, take_once};
+use crate::httpx::{HttpError, HttpResponse, HttpTags};
+use axum::body::Body;
+use axum::http::StatusCode;
+use bytes::Bytes;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::Mutex;
+use tokio_util::io::ReaderStream;
+
+/// File download, sent as an attachment unless [`FileResponse::inline`].
+pub struct FileResponse {
+    file_name: String,
+    body: Mutex<Option<Body>>,
+    content_type: String,
+    content_length: Option<u64>,
+    inline: bool,
+    response_headers: Option<Vec<(String, String)>>,
+    tags: HttpTags,
+}
+
+impl FileResponse {
+    pub fn new(file_name: &str, response_body: impl Into<Bytes>, tags: HttpTags) -> Self {
+        let response_body = response_body.into();
+        let content_length = response_body.len() as u64;
+
+        Self::with_body(
+            file_name,
+            Body::from(response_body),
+            Some(content_length),
+            tags,
+        )
+    }
+
+    /// Streams the file from disk, answering `404` when it doesn't exist.
+    pub async fn open(path: impl AsRef<Path>, tags: HttpTags) -> Result<Self, HttpError> {
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let file = tokio::fs::File::open(path).await.map_err(|error| {
+            let status_code = match error.kind() {
+                ErrorKind::NotFound => StatusCode::NOT_FOUND,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+
+            HttpError::without_body(
+                status_code,
+                format!("Failed to open file {file_name}: {error}"),
+                tags.clone(),
+            )
+            .with_source(error)
+        })?;
+        let content_length = file.metadata().await.ok().map(|metadata| metadata.len());
+
+        Ok(Self::with_body(
+            &file_name,
+            Body::from_stream(ReaderStream::new(file)),
+            content_length,
+            tags,
+        ))
+    }
+
+    fn with_body(file_name: &str, body: Body, content_length: Option<u64>, tags: HttpTags) -> Self {
+        Self {
+            file_name: file_name.to_string(),
+            body: Mutex::new(Some(body)),
+            content_type: content_type(file_name).to_string(),
+            content_length,
+            inline: false,
+            response_headers: None,
+            tags,
+        }
+    }
+
+    /// Shown by the browser instead of downloaded.
+    pub fn inline(mut self) -> Self {
+        self.inline = true;
+        self
+    }
+
+    /// Guessed from the file extension by default.
+    pub fn with_content_type(mut self, content_type: &str) -> Self {
+        self.content_type = content_type.to_string();
+        self
+    }
+
+    pub fn with_headers(mut self, response_headers: Vec<(String, String)>) -> Self {
+        self.response_headers = Some(response_headers);
+        self
+    }
+}
+
+impl HttpResponse for FileResponse {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::OK
+    }
+
+    fn error_message(&self) -> Option<String> {
+        None
+    }
+
+    fn response_body(&self) -> Option<String> {
+        None
+    }
+
+    fn response_headers(&self) -> Option<Vec<(String, String)>> {
+        let disposition = if self.inline { "inline" } else { "attachment" };
+        let disposition = if self.file_name.is_ascii() {
+            format!(
+                "{disposition}; filename=\"{}\"",
+                ascii_file_name(&self.file_name)
+            )
+        } else {
+            // RFC 6266, with a fallback for clients ignoring `filename*`
+            format!(
+                "{disposition}; filename=\"{}\"; filename*=UTF-8''{}",
+                ascii_file_name(&self.file_name),
+                percent_encode(&self.file_name)
+            )
+        };
+
+        let mut headers =
+            headers_with_default(&self.response_headers, "Content-Type", &self.content_type);
+        headers = headers_with_default(&Some(headers), "Content-Disposition", &disposition);
+        if let Some(content_length) = self.content_length {
+            headers = headers_with_default(
+                &Some(headers),
+                "Content-Length",
+                &content_length.to_string(),
+            );
+        }
+
+        Some(headers)
+    }
+
+    fn tags(&self) -> HttpTags {
+        tags_with_trace_id(&self.tags)
+    }
+
+    fn take_body(&mut self) -> Body {
+        take_once(&mut self.body)
+    }
+}
+
+/// The file name as a quoted string, without the characters that would break out of it.
+fn ascii_file_name(file_name: &str) -> String {
+    file_name
+        .chars()
+        .map(|c| {
+            if c == '"' || c == '\\' || !c.is_ascii() || c.is_ascii_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// RFC 5987 `value-chars`, every other byte percent-encoded.
+fn percent_encode(file_name: &str) -> String {
+    file_name
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'!'
+            | b'#'
+            | b'$'
+            | b'&'
+            | b'+'
+            | b'-'
+            | b'.'
+            | b'^'
+            | b'_'
+            | b'`'
+            | b'|'
+            | b'~' => (byte as char).to_string(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+fn content_type(file_name: &str) -> &'static str {
+    let extension = Path::new(file_name)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "csv" => "text/csv",
+        "gif" => "image/gif",
+        "gz" => "application/gzip",
+        "htm" | "html" => "text/html; charset=utf-8",
+        "jpeg" | "jpg" => "image/jpeg",
+        "json" => "application/json",
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "svg" => "image/svg+xml",
+        "txt" => "text/plain; charset=utf-8",
+        "webp" => "image/webp",
+        "xml" => "application/xml",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn header(response: &FileResponse, name: &str) -> Option<String> {
+        response
+            .response_headers()
+            .unwrap()
+            .into_iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value)
+    }
+
+    #[tokio::test]
+    async fn should_describe_the_file() {
+        let response = FileResponse::new("report \"2024\".PDF", "%PDF", HttpTags::default());
+
+        assert_eq!(
+            header(&response, "Content-Type").unwrap(),
+            "application/pdf"
+        );
+        assert_eq!(header(&response, "Content-Length").unwrap(), "4");
+        assert_eq!(
+            header(&response, "Content-Disposition").unwrap(),
+            "attachment; filename=\"report _2024_.PDF\""
+        );
+
+        let response = response.inline();
+        assert!(header(&response, "Content-Disposition")
+            .unwrap()
+            .starts_with("inline;"));
+
+        let response = FileResponse::new("relatório 报告.pdf", "%PDF", HttpTags::default());
+        let disposition = header(&response, "Content-Disposition").unwrap();
+        assert_eq!(
+            disposition,
+            "attachment; filename=\"relat_rio __.pdf\"; \
+             filename*=UTF-8''relat%C3%B3rio%20%E6%8A%A5%E5%91%8A.pdf"
+        );
+        assert!(axum::http::HeaderValue::try_from(disposition).is_ok());
+
+        let error = FileResponse::open("/does/not/exist.txt", HttpTags::default())
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+    }
+}
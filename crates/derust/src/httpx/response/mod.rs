@@ -1,14 +1,73 @@
 use crate::httpx::HttpTags;
+use axum::body::Body;
 use axum::http::StatusCode;
+use axum_tracing_opentelemetry::tracing_opentelemetry_instrumentation_sdk::find_current_trace_id;
+use std::sync::Mutex;
 
+pub mod bytes;
+pub mod empty;
 pub mod error;
+pub mod file;
 pub mod json;
+pub mod problem;
+pub mod redirect;
+pub mod sse;
 pub mod text;
 
 pub trait HttpResponse: Send + Sync {
     fn status_code(&self) -> StatusCode;
     fn error_message(&self) -> Option<String>;
+    /// Text body, `None` for binary and streaming responses.
     fn response_body(&self) -> Option<String>;
     fn response_headers(&self) -> Option<Vec<(String, String)>>;
     fn tags(&self) -> HttpTags;
+
+    /// Body sent to the client, taken once. Binary and streaming responses
+    /// override it, the others send [`HttpResponse::response_body`].
+    fn take_body(&mut self) -> Body {
+        Body::from(self.response_body().unwrap_or_default())
+    }
+}
+
+/// The response tags, along with the current `x-trace-id` unless already set.
+pub(crate) fn tags_with_trace_id(tags: &HttpTags) -> HttpTags {
+    let mut tags = tags.clone();
+
+    if !tags
+        .values()
+        .iter()
+        .any(|(key, _)| key.to_uppercase() == "X-TRACE-ID".to_uppercase())
+    {
+        if let Some(trace_id) = find_current_trace_id() {
+            tags.add("x-trace-id", &trace_id);
+        }
+    }
+
+    tags
+}
+
+/// The response headers, along with the given one unless already set.
+pub(crate) fn headers_with_default(
+    headers: &Option<Vec<(String, String)>>,
+    name: &str,
+    value: &str,
+) -> Vec<(String, String)> {
+    let mut headers = headers.clone().unwrap_or_default();
+
+    if !headers
+        .iter()
+        .any(|(header, _)| header.eq_ignore_ascii_case(name))
+    {
+        headers.push((name.to_string(), value.to_string()));
+    }
+
+    headers
+}
+
+/// The body of a binary or streaming response, empty once taken.
+pub(crate) fn take_once(body: &mut Mutex<Option<Body>>) -> Body {
+    body.get_mut()
+        .unwrap_or_else(|error| error.into_inner())
+        .take()
+        .unwrap_or_default()
 }
@@ -0,0 +1,181 @@
+use crate::httpx::{HttpError, HttpResponse, RedactionConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+pub const PROBLEM_JSON_CONTENT_TYPE: &str = "application/problem+json";
+
+const PROBLEM_MEMBERS: [&str; 6] = ["type", "title", "status", "detail", "instance", "trace_id"];
+
+/// RFC 7807 body of an [`HttpError`], rendered when
+/// [`AppContext::with_problem_details`](crate::httpx::AppContext::with_problem_details) is set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+    /// Extension members, such as the fields of a JSON error body.
+    #[serde(flatten)]
+    pub extensions: Map<String, Value>,
+}
+
+pub type ProblemMapper = Arc<dyn Fn(&HttpError, ProblemDetails) -> ProblemDetails + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub struct ProblemDetailsConfig {
+    /// Prefix of the `type` URIs, followed by the slugged status title, such as
+    /// `https://errors.example.com/not-found`. `about:blank` when unset.
+    pub type_base_url: Option<String>,
+    /// Uses the error messages of 5xx errors without a body as `detail`.
+    /// Off, since they may tell database or upstream internals.
+    pub expose_server_errors: bool,
+    mapper: Option<ProblemMapper>,
+}
+
+impl ProblemDetailsConfig {
+    /// Maps the default problem of an error, to give domain errors their own
+    /// `type`, `title`, status or extension members.
+    pub fn with_mapper<F>(mut self, mapper: F) -> Self
+    where
+        F: Fn(&HttpError, ProblemDetails) -> ProblemDetails + Send + Sync + 'static,
+    {
+        self.mapper = Some(Arc::new(mapper));
+        self
+    }
+
+    pub(crate) fn problem(
+        &self,
+        error: &HttpError,
+        instance: &str,
+        trace_id: Option<String>,
+        redaction: &RedactionConfig,
+    ) -> ProblemDetails {
+        let status = error.status_code();
+        let title = status.canonical_reason().unwrap_or("Unknown Error").to_string();
+
+        let mut detail = None;
+        let mut extensions = Map::new();
+
+        match error.response_json() {
+            Some(Value::Object(body)) => {
+                for (key, value) in body {
+                    match (key.as_str(), value) {
+                        ("message" | "detail", Value::String(message)) => detail = Some(message),
+                        (key, _) if PROBLEM_MEMBERS.contains(&key) => {}
+                        (_, value) => {
+                            extensions.insert(key, value);
+                        }
+                    }
+                }
+            }
+            _ => detail = error.response_body().filter(|body| !body.is_empty()),
+        }
+
+        if detail.is_none() && (!status.is_server_error() || self.expose_server_errors) {
+            detail = error.error_message();
+        }
+
+        if let Some(code) = error.code() {
+            extensions.entry("code").or_insert_with(|| Value::String(code.to_string()));
+        }
+
+        let mut extensions = Value::Object(extensions);
+        redaction.redact_json(&mut extensions);
+
+        let problem = ProblemDetails {
+            problem_type: match &self.type_base_url {
+                Some(base_url) => format!("{}/{}", base_url.trim_end_matches('/'), slug(&title)),
+                None => "about:blank".to_string(),
+            },
+            detail: Some(redaction.redact_text(&detail.unwrap_or_else(|| title.clone()))),
+            title,
+            status: status.as_u16(),
+            instance: Some(instance.to_string()),
+            trace_id,
+            extensions: match extensions {
+                Value::Object(extensions) => extensions,
+                _ => Map::new(),
+            },
+        };
+
+        match &self.mapper {
+            Some(mapper) => mapper(error, problem),
+            None => problem,
+        }
+    }
+}
+
+fn slug(title: &str) -> String {
+    title
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == ' ' || *c == '-')
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::httpx::HttpTags;
+    use axum::http::StatusCode;
+    use serde_json::json;
+
+    #[test]
+    fn should_build_the_problem_from_a_json_error() {
+        let error = HttpError::with_json(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Invalid order".to_string(),
+            json!({ "message": "Quantity must be positive", "field": "quantity", "token": "abc" }),
+            HttpTags::default(),
+        );
+        let config = ProblemDetailsConfig {
+            type_base_url: Some("https://errors.example.com/".to_string()),
+            ..ProblemDetailsConfig::default()
+        };
+
+        let problem = config.problem(&error, "/orders", Some("trace".to_string()), &RedactionConfig::default());
+
+        assert_eq!(
+            serde_json::to_value(problem).unwrap(),
+            json!({
+                "type": "https://errors.example.com/unprocessable-entity",
+                "title": "Unprocessable Entity",
+                "status": 422,
+                "detail": "Quantity must be positive",
+                "instance": "/orders",
+                "trace_id": "trace",
+                "field": "quantity",
+                "token": "***",
+            })
+        );
+    }
+
+    #[test]
+    fn should_hide_server_error_messages_and_apply_the_mapper() {
+        let error = HttpError::without_body(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Failed to connect to postgres at 10.0.0.1".to_string(),
+            HttpTags::default(),
+        );
+        let config = ProblemDetailsConfig::default().with_mapper(|_, mut problem| {
+            problem.extensions.insert("retryable".to_string(), json!(true));
+            problem
+        });
+
+        let problem = config.problem(&error, "/orders", None, &RedactionConfig::default());
+
+        assert_eq!(problem.problem_type, "about:blank");
+        assert_eq!(problem.detail.as_deref(), Some("Service Unavailable"));
+        assert_eq!(problem.extensions["retryable"], json!(true));
+    }
+}
@@ -0,0 +1,68 @@
+use crate::httpx::response::{headers_with_default, tags_with_trace_id};
+use crate::httpx::{HttpResponse, HttpTags};
+use axum::http::StatusCode;
+
+#[derive(Clone)]
+pub struct RedirectResponse {
+    status_code: StatusCode,
+    location: String,
+    response_headers: Option<Vec<(String, String)>>,
+    tags: HttpTags,
+}
+
+impl RedirectResponse {
+    /// `303 See Other`, followed with a `GET`, such as after a form submission.
+    pub fn to(location: &str, tags: HttpTags) -> Self {
+        Self::new(StatusCode::SEE_OTHER, location, tags)
+    }
+
+    /// `307 Temporary Redirect`, keeping the method and body.
+    pub fn temporary(location: &str, tags: HttpTags) -> Self {
+        Self::new(StatusCode::TEMPORARY_REDIRECT, location, tags)
+    }
+
+    /// `308 Permanent Redirect`, keeping the method and body.
+    pub fn permanent(location: &str, tags: HttpTags) -> Self {
+        Self::new(StatusCode::PERMANENT_REDIRECT, location, tags)
+    }
+
+    fn new(status_code: StatusCode, location: &str, tags: HttpTags) -> Self {
+        Self {
+            status_code,
+            location: location.to_string(),
+            response_headers: None,
+            tags,
+        }
+    }
+
+    pub fn with_headers(mut self, response_headers: Vec<(String, String)>) -> Self {
+        self.response_headers = Some(response_headers);
+        self
+    }
+}
+
+impl HttpResponse for RedirectResponse {
+    fn status_code(&self) -> StatusCode {
+        self.status_code
+    }
+
+    fn error_message(&self) -> Option<String> {
+        None
+    }
+
+    fn response_body(&self) -> Option<String> {
+        None
+    }
+
+    fn response_headers(&self) -> Option<Vec<(String, String)>> {
+        Some(headers_with_default(
+            &self.response_headers,
+            "Location",
+            &self.location,
+        ))
+    }
+
+    fn tags(&self) -> HttpTags {
+        tags_with_trace_id(&self.tags)
+    }
+}
@@ -0,0 +1,75 @@
+use crate::httpx::response::{headers_with_default, tags_with_trace_id, take_once};
+use crate::httpx::{HttpResponse, HttpTags};
+use axum::body::Body;
+use axum::http::StatusCode;
+use axum::response::sse::{KeepAlive, Sse};
+use axum::response::IntoResponse;
+use axum::BoxError;
+use futures_util::Stream;
+use std::sync::Mutex;
+
+pub use axum::response::sse::Event;
+
+/// Server-sent events, with keep-alive comments every 15 seconds.
+pub struct SseResponse {
+    body: Mutex<Option<Body>>,
+    response_headers: Option<Vec<(String, String)>>,
+    tags: HttpTags,
+}
+
+impl SseResponse {
+    pub fn new<S, E>(stream: S, tags: HttpTags) -> Self
+    where
+        S: Stream<Item = Result<Event, E>> + Send + 'static,
+        E: Into<BoxError>,
+    {
+        let body = Sse::new(stream)
+            .keep_alive(KeepAlive::default())
+            .into_response()
+            .into_body();
+
+        Self {
+            body: Mutex::new(Some(body)),
+            response_headers: None,
+            tags,
+        }
+    }
+
+    pub fn with_headers(mut self, response_headers: Vec<(String, String)>) -> Self {
+        self.response_headers = Some(response_headers);
+        self
+    }
+}
+
+impl HttpResponse for SseResponse {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::OK
+    }
+
+    fn error_message(&self) -> Option<String> {
+        None
+    }
+
+    fn response_body(&self) -> Option<String> {
+        None
+    }
+
+    fn response_headers(&self) -> Option<Vec<(String, String)>> {
+        let headers =
+            headers_with_default(&self.response_headers, "Content-Type", "text/event-stream");
+
+        Some(headers_with_default(
+            &Some(headers),
+            "Cache-Control",
+            "no-cache",
+        ))
+    }
+
+    fn tags(&self) -> HttpTags {
+        tags_with_trace_id(&self.tags)
+    }
+
+    fn take_body(&mut self) -> Body {
+        take_once(&mut self.body)
+    }
+}
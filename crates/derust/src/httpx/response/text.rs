@@ -1,6 +1,6 @@
+use crate::httpx::response::{headers_with_default, tags_with_trace_id};
 use crate::httpx::{HttpResponse, HttpTags};
 use axum::http::StatusCode;
-use axum_tracing_opentelemetry::tracing_opentelemetry_instrumentation_sdk::find_current_trace_id;
 
 #[derive(Clone)]
 pub struct TextResponse {
@@ -40,31 +40,14 @@ impl HttpResponse for TextResponse {
     }
 
     fn response_headers(&self) -> Option<Vec<(String, String)>> {
-        let mut headers: Vec<(String, String)> = self.response_headers.clone().unwrap_or_default();
-
-        if !headers
-            .iter()
-            .any(|(name, _)| name.to_uppercase() == "Content-Type".to_uppercase())
-        {
-            headers.push(("Content-Type".to_string(), "text/plain".to_string()));
-        }
-
-        Some(headers)
+        Some(headers_with_default(
+            &self.response_headers,
+            "Content-Type",
+            "text/plain",
+        ))
     }
 
     fn tags(&self) -> HttpTags {
-        let mut tags = self.tags.clone();
-
-        if !tags
-            .values()
-            .iter()
-            .any(|(key, _)| key.to_uppercase() == "X-TRACE-ID".to_uppercase())
-        {
-            if let Some(trace_id) = find_current_trace_id() {
-                tags.add("x-trace-id", &trace_id);
-            }
-        }
-
-        tags
+        tags_with_trace_id(&self.tags)
     }
 }
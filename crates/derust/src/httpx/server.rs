@@ -1,106 +1,512 @@
 use axum::Router;
-use std::net::{Ipv4Addr, SocketAddr};
+use std::env;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+#[cfg(feature = "start_test")]
 use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
 
-use crate::httpx::extension::apply_middlewares;
-use crate::httpx::AppContext;
-use crate::shutdown_signal;
-use tracing::info;
-use wg::WaitGroup;
+use crate::httpx::bind::ServerListener;
+use crate::httpx::config::DEFAULT_PORT;
+use crate::httpx::extension::{
+    apply_management_middlewares, apply_middlewares, management_routes,
+};
+use crate::httpx::health::{builtin_indicators, HealthIndicators};
+use crate::httpx::shutdown::{
+    coordinate, run_hooks, stop_components, wait_components_or_shutdown, ShutdownHook,
+};
+#[cfg(feature = "tls")]
+use crate::httpx::tls::TlsSetup;
+use crate::httpx::worker::run_worker;
+use crate::httpx::{AppContext, BackgroundWorker, BindAddress, HealthConfig, HealthIndicator, ShutdownConfig};
+#[cfg(feature = "tls")]
+use crate::httpx::{TlsConfig, TlsConnectInfo};
+use tracing::{error, info};
 
 #[cfg(feature = "outbox")]
 use crate::outboxx;
 #[cfg(feature = "outbox")]
+use crate::outboxx::OutboxHealthIndicator;
+#[cfg(feature = "outbox")]
 use outbox_pattern_processor::outbox_resources::OutboxProcessorResources;
 
+#[cfg(feature = "growthbook")]
+use crate::growthbookx;
+
 #[cfg(feature = "sqs")]
-use crate::awsx::sqsx::SqsQueueConsumer;
+use crate::awsx::sqsx::{SqsHealthIndicator, SqsQueueConsumer};
+#[cfg(any(feature = "outbox", feature = "sqs"))]
+use std::time::Duration;
 
-pub async fn start<T>(
-    port: u16,
+const MANAGEMENT_PORT_ENV_NAME: &str = "MANAGEMENT_PORT";
+
+type BackgroundTask = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Entry point of the application server. See [`Server::builder`].
+pub struct Server;
+
+impl Server {
+    /// Starts building a server for the given context and router.
+    ///
+    /// Every optional component (outbox processor, SQS consumers, background
+    /// tasks) is registered through a builder method, so enabling a cargo
+    /// feature only adds methods and never changes existing call sites.
+    pub fn builder<T>(context: AppContext<T>, router: Router<AppContext<T>>) -> ServerBuilder<T>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        ServerBuilder {
+            context,
+            router,
+            port: DEFAULT_PORT,
+            bind_address: BindAddress::default(),
+            management_port: None,
+            management_routes: Router::new(),
+            health_indicators: vec![],
+            health_config: HealthConfig::default(),
+            enable_web_socket: false,
+            #[cfg(feature = "tls")]
+            tls: None,
+            shutdown_config: ShutdownConfig::default(),
+            shutdown_hooks: vec![],
+            #[cfg(feature = "outbox")]
+            outbox: None,
+            #[cfg(all(feature = "outbox", any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics")))]
+            outbox_metrics_monitor: None,
+            #[cfg(feature = "sqs")]
+            sqs_consumers: vec![],
+            background_tasks: vec![],
+            background_workers: vec![],
+        }
+    }
+}
+
+pub struct ServerBuilder<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
     context: AppContext<T>,
     router: Router<AppContext<T>>,
+    port: u16,
+    bind_address: BindAddress,
+    management_port: Option<u16>,
+    management_routes: Router<AppContext<T>>,
+    health_indicators: HealthIndicators<T>,
+    health_config: HealthConfig,
     enable_web_socket: bool,
-    #[cfg(feature = "outbox")] outbox_processor_resources: OutboxProcessorResources,
-    #[cfg(feature = "outbox")] outbox_metrics_monitor_enabled: bool,
-    #[cfg(feature = "outbox")] outbox_metrics_monitor_interval_in_secs: Option<u64>,
-    #[cfg(feature = "sqs")] sqs_consumers: Vec<SqsQueueConsumer<T>>,
-) -> Result<(), Box<dyn std::error::Error>>
+    #[cfg(feature = "tls")]
+    tls: Option<TlsConfig>,
+    shutdown_config: ShutdownConfig,
+    shutdown_hooks: Vec<ShutdownHook>,
+    #[cfg(feature = "outbox")]
+    outbox: Option<OutboxProcessorResources>,
+    #[cfg(all(feature = "outbox", any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics")))]
+    outbox_metrics_monitor: Option<Option<u64>>,
+    #[cfg(feature = "sqs")]
+    sqs_consumers: Vec<SqsQueueConsumer<T>>,
+    background_tasks: Vec<BackgroundTask>,
+    background_workers: Vec<Arc<dyn BackgroundWorker<T>>>,
+}
+
+impl<T> ServerBuilder<T>
 where
     T: Clone + Send + Sync + 'static,
 {
-    let wg = WaitGroup::new();
-    let http_router = apply_middlewares(router, context.clone());
-    tokio::spawn(start_http_server(wg.add(1), port, http_router, enable_web_socket));
+    /// Port of the HTTP server. Defaults to [`DEFAULT_PORT`].
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Address of the HTTP server. Defaults to every IPv4 address (`0.0.0.0`).
+    pub fn bind_address(mut self, address: BindAddress) -> Self {
+        self.bind_address = address;
+        self
+    }
+
+    /// Serves health, metrics and any [`ServerBuilder::with_management_routes`]
+    /// on a separate listener, outside the business API middleware stack.
+    /// Defaults to the `MANAGEMENT_PORT` env var, failing the startup when it
+    /// isn't a port; without it, those endpoints are served on the main port.
+    pub fn management_port(mut self, port: u16) -> Self {
+        self.management_port = Some(port);
+        self
+    }
+
+    /// Additional operational endpoints, served alongside health and metrics.
+    pub fn with_management_routes(mut self, routes: Router<AppContext<T>>) -> Self {
+        self.management_routes = self.management_routes.merge(routes);
+        self
+    }
+
+    /// Adds a check to the readiness probe (`/health/readiness` and `/health`).
+    pub fn with_health_indicator<I>(mut self, indicator: I) -> Self
+    where
+        I: HealthIndicator<T> + 'static,
+    {
+        self.health_indicators.push(Arc::new(indicator));
+        self
+    }
+
+    /// Thresholds of the built-in health indicators.
+    pub fn with_health_config(mut self, config: HealthConfig) -> Self {
+        self.health_config = config;
+        self
+    }
+
+    /// Serves the router with `ConnectInfo<SocketAddr>`, required by web socket handlers.
+    pub fn with_web_socket(mut self) -> Self {
+        self.enable_web_socket = true;
+        self
+    }
+
+    /// Terminates TLS on the HTTP server port. Handlers get the connection
+    /// info and the mTLS client identity through `ConnectInfo<TlsConnectInfo>`
+    /// or the [`PeerIdentity`](crate::httpx::PeerIdentity) extractor, also
+    /// when web sockets are enabled. The management server stays plain HTTP.
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, config: TlsConfig) -> Self {
+        self.tls = Some(config);
+        self
+    }
 
+    /// Pre-stop delay and hard deadline of the graceful shutdown.
+    pub fn with_shutdown_config(mut self, config: ShutdownConfig) -> Self {
+        self.shutdown_config = config;
+        self
+    }
+
+    /// Registers a cleanup step (flush metrics, close pools, commit offsets...)
+    /// run once every component has stopped. Hooks run in registration order
+    /// and are skipped once the shutdown deadline is reached.
+    pub fn with_shutdown_hook<F, Fut>(mut self, name: &str, hook: F) -> Self
+    where
+        F: FnOnce(AppContext<T>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let context = self.context.clone();
+        self.shutdown_hooks
+            .push((name.to_string(), Box::new(move || Box::pin(hook(context)))));
+        self
+    }
+
+    /// Runs the embedded outbox-pattern-processor alongside the HTTP server.
     #[cfg(feature = "outbox")]
-    tokio::spawn(outboxx::run(wg.add(1), context.clone(), outbox_processor_resources, outbox_metrics_monitor_enabled, outbox_metrics_monitor_interval_in_secs));
+    pub fn with_outbox(mut self, resources: OutboxProcessorResources) -> Self {
+        self.outbox = Some(resources);
+        self
+    }
+
+    /// Periodically publishes the outbox processing delay as a gauge.
+    /// Only takes effect together with [`ServerBuilder::with_outbox`].
+    #[cfg(all(feature = "outbox", any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics")))]
+    pub fn with_outbox_metrics_monitor(mut self, interval_in_secs: Option<u64>) -> Self {
+        self.outbox_metrics_monitor = Some(interval_in_secs);
+        self
+    }
 
     #[cfg(feature = "sqs")]
-    tokio::spawn(crate::awsx::sqsx::run(wg.add(1), context.clone(), sqs_consumers));
+    pub fn with_sqs_consumers(mut self, consumers: Vec<SqsQueueConsumer<T>>) -> Self {
+        self.sqs_consumers.extend(consumers);
+        self
+    }
 
-    wg.wait();
+    /// Spawns a task alongside the HTTP server. Tasks still running once the
+    /// server has shut down are aborted.
+    pub fn with_background_task<F>(mut self, task: F) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.background_tasks.push(Box::pin(task));
+        self
+    }
 
-    info!("Shutdown completed!");
+    /// Runs a [`BackgroundWorker`] alongside the HTTP server. The server waits
+    /// for the worker to return after the shutdown signal.
+    pub fn with_background_worker<W>(mut self, worker: W) -> Self
+    where
+        W: BackgroundWorker<T> + 'static,
+    {
+        self.background_workers.push(Arc::new(worker));
+        self
+    }
 
-    Ok(())
-}
+    /// Starts the HTTP server and every registered component, returning once
+    /// all of them have stopped after the shutdown signal. Fails without
+    /// starting anything when a listener can't be bound.
+    pub async fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        self.serve(false).await
+    }
 
-#[cfg(feature = "outbox")]
-pub async fn start_only_api<T>(
-    port: u16,
-    context: AppContext<T>,
-    router: Router<AppContext<T>>,
-    enable_web_socket: bool,
-) -> Result<(), Box<dyn std::error::Error>>
-where
-    T: Clone + Send + Sync + 'static,
-{
-    let wg = WaitGroup::new();
-    let http_router = apply_middlewares(router, context.clone());
-    tokio::spawn(start_http_server(wg.add(1), port, http_router, enable_web_socket));
+    /// Starts only the HTTP server, ignoring any registered outbox processor,
+    /// SQS consumer, background task or background worker. Shutdown hooks
+    /// still run.
+    pub async fn run_only_api(self) -> Result<(), Box<dyn std::error::Error>> {
+        self.serve(true).await
+    }
 
-    wg.wait();
+    /// Serves only the HTTP router on an already bound listener, usually
+    /// `127.0.0.1:0` in integration tests. The configured port is ignored and
+    /// the server stops on [`ShutdownHandle::trigger`](crate::httpx::ShutdownHandle::trigger).
+    #[cfg(feature = "start_test")]
+    pub async fn run_test(self, listener: TcpListener) -> std::io::Result<()> {
+        let shutdown = self.context.shutdown().clone();
+        tokio::spawn(coordinate(shutdown.clone(), self.shutdown_config));
+        #[cfg(feature = "growthbook")]
+        tokio::spawn(growthbookx::watch_refresh(
+            self.context.growth_book().clone(),
+            self.context.growth_book_refresh().clone(),
+            shutdown.token(),
+        ));
 
-    info!("Shutdown completed!");
+        let mut health_indicators = builtin_indicators(&self.context, &self.health_config);
+        health_indicators.extend(self.health_indicators);
 
-    Ok(())
-}
+        let router = self
+            .router
+            .merge(management_routes(health_indicators))
+            .merge(self.management_routes);
+        let http_router = apply_middlewares(router, self.context);
 
-#[cfg(feature = "start_test")]
-pub async fn start_test<T>(
-    context: AppContext<T>,
-    router: Router<AppContext<T>>,
-    listener: TcpListener,
-) -> std::io::Result<()>
-where
-    T: Clone + Send + Sync + 'static,
-{
-    let http_router = apply_middlewares(router, context);
+        #[cfg(feature = "tls")]
+        if let Some(config) = self.tls {
+            let listener = TlsSetup::new(config)
+                .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidInput, error.to_string()))?
+                .listen(listener, shutdown.token())?;
 
-    axum::serve(listener, http_router.into_make_service())
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-}
+            return axum::serve(listener, http_router.into_make_service_with_connect_info::<TlsConnectInfo>())
+                .with_graceful_shutdown(shutdown.token().cancelled_owned())
+                .await;
+        }
 
-async fn start_http_server(wg: WaitGroup, port: u16, router: Router<()>, enable_web_socket: bool) {
-    info!("Started http server on port {}", port);
+        axum::serve(listener, http_router.into_make_service())
+            .with_graceful_shutdown(shutdown.token().cancelled_owned())
+            .await
+    }
 
-    let addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, port));
+    async fn serve(self, only_api: bool) -> Result<(), Box<dyn std::error::Error>> {
+        // invalid certificates and bind failures fail the startup instead of
+        // running the remaining components without a server
+        #[cfg(feature = "tls")]
+        let tls = self.tls.map(TlsSetup::new).transpose()?;
+        #[cfg(feature = "tls")]
+        if tls.is_some() && matches!(self.bind_address, BindAddress::Unix(_)) {
+            return Err("TLS is not supported on Unix socket listeners".into());
+        }
 
-    if let Ok(listener) = TcpListener::bind(addr).await {
-        if enable_web_socket {
-            let _ = axum::serve(listener, router.into_make_service_with_connect_info::<SocketAddr>())
-                .with_graceful_shutdown(shutdown_signal())
-                .await;
+        let listener = self.bind_address.bind(self.port).await?;
+        let management_port = match self.management_port {
+            Some(management_port) => Some(management_port),
+            None => management_port_from_env()?,
+        };
+        let management_listener = match management_port {
+            Some(management_port) => Some((
+                self.bind_address.for_management().bind(management_port).await?,
+                self.bind_address.for_management().describe(management_port),
+            )),
+            None => None,
+        };
+
+        let shutdown = self.context.shutdown().clone();
+        let token = shutdown.token();
+        tokio::spawn(coordinate(shutdown.clone(), self.shutdown_config.clone()));
+        #[cfg(feature = "tls")]
+        let listener = match (listener, tls) {
+            (ServerListener::Tcp(listener), Some(tls)) => ServerListener::Tls(tls.listen(listener, token.clone())?),
+            (listener, _) => listener,
+        };
+        #[cfg(feature = "growthbook")]
+        tokio::spawn(growthbookx::watch_refresh(
+            self.context.growth_book().clone(),
+            self.context.growth_book_refresh().clone(),
+            token.clone(),
+        ));
+
+        let mut components: Vec<(String, JoinHandle<()>)> = vec![];
+        let mut background_tasks = vec![];
+
+        let mut health_indicators = builtin_indicators(&self.context, &self.health_config);
+
+        if !only_api {
+            #[cfg(feature = "outbox")]
+            if self.outbox.is_some() {
+                health_indicators.push(Arc::new(OutboxHealthIndicator::new(Duration::from_secs(
+                    self.health_config.outbox_max_delay_in_secs,
+                ))));
+            }
+
+            #[cfg(feature = "sqs")]
+            if !self.sqs_consumers.is_empty() {
+                health_indicators.push(Arc::new(SqsHealthIndicator::new(
+                    &self.sqs_consumers,
+                    Duration::from_secs(self.health_config.sqs_max_poll_age_in_secs),
+                )));
+            }
+        }
+
+        health_indicators.extend(self.health_indicators);
+
+        let management_router = management_routes(health_indicators).merge(self.management_routes);
+
+        let http_router = if let Some((management_listener, management_address)) = management_listener {
+            let management_router =
+                apply_management_middlewares(management_router, self.context.clone());
+            components.push((
+                "management server".to_string(),
+                tokio::spawn(start_http_server(
+                    "management server",
+                    management_address,
+                    management_listener,
+                    management_router,
+                    false,
+                    token.clone(),
+                )),
+            ));
+
+            apply_middlewares(self.router, self.context.clone())
         } else {
-            let _ = axum::serve(listener, router.into_make_service())
-                .with_graceful_shutdown(shutdown_signal())
-                .await;
+            apply_middlewares(self.router.merge(management_router), self.context.clone())
+        };
+
+        components.push((
+            "http server".to_string(),
+            tokio::spawn(start_http_server(
+                "http server",
+                self.bind_address.describe(self.port),
+                listener,
+                http_router,
+                self.enable_web_socket,
+                token.clone(),
+            )),
+        ));
+
+        if !only_api {
+            #[cfg(feature = "outbox")]
+            if let Some(resources) = self.outbox {
+                components.push((
+                    "outbox processor".to_string(),
+                    tokio::spawn(outboxx::run(
+                        self.context.clone(),
+                        resources,
+                        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
+                        self.outbox_metrics_monitor.is_some(),
+                        #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
+                        self.outbox_metrics_monitor.flatten(),
+                    )),
+                ));
+            }
+
+            #[cfg(feature = "sqs")]
+            if !self.sqs_consumers.is_empty() {
+                components.push((
+                    "sqs consumers".to_string(),
+                    tokio::spawn(crate::awsx::sqsx::run(
+                        self.context.clone(),
+                        self.sqs_consumers,
+                    )),
+                ));
+            }
+
+            for worker in self.background_workers {
+                let name = format!("background worker {}", worker.name());
+                let handle = tokio::spawn({
+                    let context = self.context.clone();
+                    let token = token.clone();
+                    async move {
+                        run_worker(context, worker, token).await;
+                    }
+                });
+                components.push((name, handle));
+            }
+
+            background_tasks = self
+                .background_tasks
+                .into_iter()
+                .map(tokio::spawn)
+                .collect::<Vec<_>>();
+        }
+
+        wait_components_or_shutdown(&mut components, &shutdown).await;
+
+        let deadline = Instant::now() + self.shutdown_config.timeout();
+        stop_components(&mut components, deadline).await;
+
+        for task in background_tasks {
+            task.abort();
         }
+
+        run_hooks(self.shutdown_hooks, deadline).await;
+
+        // the last export interval would be lost otherwise
+        #[cfg(feature = "otlp_metrics")]
+        if let Err(error) = self.context.meter_provider().force_flush() {
+            error!("Failed to flush OTLP metrics: {error}");
+        }
+
+        info!("Shutdown completed!");
+
+        Ok(())
     }
+}
 
-    wg.done();
+async fn start_http_server(
+    name: &'static str,
+    address: String,
+    listener: ServerListener,
+    router: Router<()>,
+    enable_web_socket: bool,
+    shutdown: CancellationToken,
+) {
+    let result = match listener {
+        #[cfg(feature = "tls")]
+        ServerListener::Tls(listener) => {
+            info!("Started {name} on {address} with TLS");
+
+            axum::serve(listener, router.into_make_service_with_connect_info::<TlsConnectInfo>())
+                .with_graceful_shutdown(shutdown.cancelled_owned())
+                .await
+        }
+        ServerListener::Tcp(listener) if enable_web_socket => {
+            info!("Started {name} on {address}");
+
+            axum::serve(listener, router.into_make_service_with_connect_info::<SocketAddr>())
+                .with_graceful_shutdown(shutdown.cancelled_owned())
+                .await
+        }
+        ServerListener::Tcp(listener) => {
+            info!("Started {name} on {address}");
+
+            axum::serve(listener, router.into_make_service())
+                .with_graceful_shutdown(shutdown.cancelled_owned())
+                .await
+        }
+        ServerListener::Unix(listener) => {
+            info!("Started {name} on {address}");
 
-    info!("Http server stopped!");
+            axum::serve(listener, router.into_make_service())
+                .with_graceful_shutdown(shutdown.cancelled_owned())
+                .await
+        }
+    };
+
+    match result {
+        Ok(_) => info!("{name} stopped!"),
+        Err(error) => error!("{name} failed: {error}"),
+    }
+}
+
+/// The `MANAGEMENT_PORT` env var, failing on a value that isn't a port.
+fn management_port_from_env() -> Result<Option<u16>, String> {
+    match env::var(MANAGEMENT_PORT_ENV_NAME) {
+        Ok(port) => port
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("Invalid {MANAGEMENT_PORT_ENV_NAME} {port}, expected a port number")),
+        Err(_) => Ok(None),
+    }
 }
@@ -0,0 +1,213 @@
+use crate::shutdown_signal;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+const DEFAULT_PRE_STOP_DELAY_IN_MILLIS: u64 = 0;
+const DEFAULT_SHUTDOWN_TIMEOUT_IN_MILLIS: u64 = 30000;
+
+pub(crate) type ShutdownHook = (
+    String,
+    Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>,
+);
+
+/// Graceful shutdown timings, loadable through `envx::load_app_config`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShutdownConfig {
+    /// Time between the shutdown signal and stopping the components. During
+    /// this period `/health` reports `DRAINING` so the load balancer can
+    /// deregister the instance while requests are still served.
+    pub pre_stop_delay_in_millis: u64,
+    /// Maximum time for components and shutdown hooks to stop once the
+    /// pre-stop delay is over. Anything still running afterwards is aborted.
+    pub timeout_in_millis: u64,
+}
+
+impl ShutdownConfig {
+    pub fn pre_stop_delay(&self) -> Duration {
+        Duration::from_millis(self.pre_stop_delay_in_millis)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_in_millis)
+    }
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            pre_stop_delay_in_millis: DEFAULT_PRE_STOP_DELAY_IN_MILLIS,
+            timeout_in_millis: DEFAULT_SHUTDOWN_TIMEOUT_IN_MILLIS,
+        }
+    }
+}
+
+/// Shutdown state shared by every component through [`AppContext::shutdown`](crate::httpx::AppContext::shutdown).
+#[derive(Clone, Default)]
+pub struct ShutdownHandle {
+    draining: CancellationToken,
+    token: CancellationToken,
+}
+
+impl ShutdownHandle {
+    /// Cancelled once the pre-stop delay is over and components must stop.
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Whether a shutdown was requested, including the pre-stop delay.
+    pub fn is_draining(&self) -> bool {
+        self.draining.is_cancelled()
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Starts the shutdown as if SIGTERM had been received.
+    pub fn trigger(&self) {
+        self.draining.cancel();
+    }
+}
+
+/// Waits for SIGTERM/Ctrl+C (or [`ShutdownHandle::trigger`]), reports draining
+/// during the pre-stop delay and then cancels the shutdown token.
+pub(crate) async fn coordinate(handle: ShutdownHandle, config: ShutdownConfig) {
+    tokio::select! {
+        _ = shutdown_signal() => {},
+        _ = handle.draining.cancelled() => {},
+        _ = handle.token.cancelled() => {},
+    }
+
+    handle.draining.cancel();
+
+    if !config.pre_stop_delay().is_zero() && !handle.token.is_cancelled() {
+        info!(
+            "Draining for {}ms before stopping components",
+            config.pre_stop_delay_in_millis
+        );
+        tokio::select! {
+            _ = tokio::time::sleep(config.pre_stop_delay()) => {},
+            _ = handle.token.cancelled() => {},
+        }
+    }
+
+    handle.token.cancel();
+}
+
+/// Waits for every component to stop, aborting the ones still running once
+/// `deadline` is reached.
+pub(crate) async fn stop_components(components: &mut [(String, JoinHandle<()>)], deadline: Instant) {
+    if tokio::time::timeout_at(deadline, join_all(components)).await.is_err() {
+        for (name, handle) in components.iter() {
+            if !handle.is_finished() {
+                warn!("Shutdown timeout reached, aborting {name}");
+                handle.abort();
+            }
+        }
+    }
+}
+
+/// Runs the shutdown hooks in registration order, skipping the remaining ones
+/// once `deadline` is reached.
+pub(crate) async fn run_hooks(hooks: Vec<ShutdownHook>, deadline: Instant) {
+    for (name, hook) in hooks {
+        info!("Running shutdown hook {name}");
+
+        if tokio::time::timeout_at(deadline, hook()).await.is_err() {
+            warn!("Shutdown timeout reached while running shutdown hook {name}");
+            break;
+        }
+    }
+}
+
+/// Waits until every component has stopped or the shutdown token is cancelled,
+/// whichever comes first.
+pub(crate) async fn wait_components_or_shutdown(
+    components: &mut [(String, JoinHandle<()>)],
+    handle: &ShutdownHandle,
+) {
+    tokio::select! {
+        _ = join_all(components) => {},
+        _ = handle.token.cancelled() => {},
+    }
+}
+
+async fn join_all(components: &mut [(String, JoinHandle<()>)]) {
+    for (_, component) in components.iter_mut() {
+        if !component.is_finished() {
+            let _ = component.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test(start_paused = true)]
+    async fn should_drain_before_cancelling_the_token() {
+        let handle = ShutdownHandle::default();
+        let config = ShutdownConfig {
+            pre_stop_delay_in_millis: 1000,
+            timeout_in_millis: 1000,
+        };
+
+        let coordinator = tokio::spawn(coordinate(handle.clone(), config));
+        handle.trigger();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        assert!(handle.is_draining());
+        assert!(!handle.is_shutting_down());
+
+        coordinator.await.unwrap();
+
+        assert!(handle.is_shutting_down());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_abort_components_after_the_deadline() {
+        let mut components = vec![
+            ("fast".to_string(), tokio::spawn(async {})),
+            (
+                "stuck".to_string(),
+                tokio::spawn(tokio::time::sleep(Duration::from_secs(60))),
+            ),
+        ];
+
+        stop_components(&mut components, Instant::now() + Duration::from_secs(1)).await;
+
+        let stuck = components.pop().unwrap().1.await;
+        assert!(stuck.unwrap_err().is_cancelled());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_run_hooks_in_order_until_the_deadline() {
+        let calls = Arc::new(Mutex::new(vec![]));
+
+        let hook = |name: &'static str, delay: u64| -> ShutdownHook {
+            let calls = calls.clone();
+            (
+                name.to_string(),
+                Box::new(move || {
+                    Box::pin(async move {
+                        tokio::time::sleep(Duration::from_secs(delay)).await;
+                        calls.lock().unwrap().push(name);
+                    })
+                }),
+            )
+        };
+
+        let hooks = vec![hook("first", 0), hook("second", 0), hook("slow", 10), hook("never", 0)];
+        run_hooks(hooks, Instant::now() + Duration::from_secs(1)).await;
+
+        assert_eq!(*calls.lock().unwrap(), vec!["first", "second"]);
+    }
+}
@@ -1,15 +1,26 @@
+use crate::httpx::middlewares::request_id::{current_request_id, REQUEST_ID_TAG};
 use std::collections::HashMap;
 
-#[derive(Clone, Debug, Default)]
+/// Tags logged along with a request. Created within a request or an SQS
+/// message, they carry its `request_id`.
+#[derive(Clone, Debug)]
 pub struct HttpTags(HashMap<String, String>);
 
+impl Default for HttpTags {
+    fn default() -> Self {
+        Self(with_capacity(0))
+    }
+}
+
 impl HttpTags {
     pub fn error(error: Box<dyn std::error::Error>) -> Self {
-        Self(HashMap::from([("error".to_string(), error.to_string())]))
+        Self::error_message(&error.to_string())
     }
 
     pub fn error_message(message: &str) -> Self {
-        Self(HashMap::from([("error".to_string(), message.to_string())]))
+        let mut map = with_capacity(1);
+        map.insert("error".to_string(), message.to_string());
+        Self(map)
     }
 
     pub fn add(&mut self, k: &str, v: &str) {
@@ -21,9 +32,17 @@ impl HttpTags {
     }
 }
 
+fn with_capacity(capacity: usize) -> HashMap<String, String> {
+    let mut map = HashMap::with_capacity(capacity + 1);
+    if let Some(request_id) = current_request_id() {
+        map.insert(REQUEST_ID_TAG.to_string(), request_id);
+    }
+    map
+}
+
 impl<const N: usize> From<[(String, String); N]> for HttpTags {
     fn from(arr: [(String, String); N]) -> Self {
-        let mut map = HashMap::with_capacity(N);
+        let mut map = with_capacity(N);
         for (k, v) in arr {
             map.insert(k, v);
         }
@@ -33,7 +52,7 @@ impl<const N: usize> From<[(String, String); N]> for HttpTags {
 
 impl<const N: usize> From<[(&str, String); N]> for HttpTags {
     fn from(arr: [(&str, String); N]) -> Self {
-        let mut map = HashMap::with_capacity(N);
+        let mut map = with_capacity(N);
         for (k, v) in arr {
             map.insert(k.to_string(), v);
         }
@@ -43,7 +62,7 @@ impl<const N: usize> From<[(&str, String); N]> for HttpTags {
 
 impl<const N: usize> From<[(&str, &str); N]> for HttpTags {
     fn from(arr: [(&str, &str); N]) -> Self {
-        let mut map = HashMap::with_capacity(N);
+        let mut map = with_capacity(N);
         for (k, v) in arr {
             map.insert(k.to_string(), v.to_string());
         }
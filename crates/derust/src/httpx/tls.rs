@@ -0,0 +1,630 @@
+use crate::httpx::{HttpError, HttpTags};
+use axum::extract::connect_info::Connected;
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::serve::{IncomingStream, Listener};
+use rustls::crypto::{ring, CryptoProvider};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier};
+use rustls::sign::CertifiedKey;
+use rustls::{RootCertStore, ServerConfig};
+use serde::{Deserialize, Serialize};
+use simple_asn1::{ASN1Block, ASN1Class, BigUint, OID};
+use std::fmt::{Debug, Formatter};
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info};
+
+const HANDSHAKE_TIMEOUT_IN_SECS: u64 = 10;
+const ACCEPT_BACKLOG: usize = 1024;
+
+/// TLS termination of the HTTP server, loadable through `envx::load_app_config`.
+///
+/// Certificate and key are read from `cert_path`/`key_path` or, when those are
+/// not set, from the inline `cert_pem`/`key_pem` values. Setting a client CA
+/// enables mTLS.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    pub cert_path: Option<String>,
+    pub key_path: Option<String>,
+    pub cert_pem: Option<String>,
+    pub key_pem: Option<String>,
+    /// CA bundle used to verify client certificates.
+    pub client_ca_path: Option<String>,
+    pub client_ca_pem: Option<String>,
+    /// Accepts clients without certificate, still verifying the ones presenting it.
+    pub client_auth_optional: bool,
+    /// Interval to reload `cert_path`/`key_path`, so renewed certificates are
+    /// served without restarting. Disabled when not set.
+    pub reload_interval_in_secs: Option<u64>,
+}
+
+impl Debug for TlsConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsConfig")
+            .field("cert_path", &self.cert_path)
+            .field("key_path", &self.key_path)
+            .field("cert_pem", &self.cert_pem.as_ref().map(|_| "***"))
+            .field("key_pem", &self.key_pem.as_ref().map(|_| "***"))
+            .field("client_ca_path", &self.client_ca_path)
+            .field("client_ca_pem", &self.client_ca_pem.as_ref().map(|_| "***"))
+            .field("client_auth_optional", &self.client_auth_optional)
+            .field("reload_interval_in_secs", &self.reload_interval_in_secs)
+            .finish()
+    }
+}
+
+impl TlsConfig {
+    fn read_pem(path: &Option<String>, inline: &Option<String>, name: &str) -> Result<Option<Vec<u8>>, String> {
+        match (path, inline) {
+            (Some(path), _) => std::fs::read(path)
+                .map(Some)
+                .map_err(|error| format!("Failed to read TLS {name} from {path}: {error}")),
+            (None, Some(pem)) => Ok(Some(pem.as_bytes().to_vec())),
+            (None, None) => Ok(None),
+        }
+    }
+
+    fn certified_key(&self, provider: &CryptoProvider) -> Result<CertifiedKey, String> {
+        let cert = Self::read_pem(&self.cert_path, &self.cert_pem, "certificate")?
+            .ok_or("TLS certificate is not configured")?;
+        let key = Self::read_pem(&self.key_path, &self.key_pem, "key")?.ok_or("TLS key is not configured")?;
+
+        let certs = CertificateDer::pem_slice_iter(&cert)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| format!("Invalid TLS certificate: {error}"))?;
+        if certs.is_empty() {
+            return Err("TLS certificate has no PEM certificate section".to_string());
+        }
+
+        let key = PrivateKeyDer::from_pem_slice(&key).map_err(|error| format!("Invalid TLS key: {error}"))?;
+
+        CertifiedKey::from_der(certs, key, provider).map_err(|error| format!("Invalid TLS certificate/key pair: {error}"))
+    }
+
+    fn server_config(&self, resolver: Arc<CertificateResolver>) -> Result<ServerConfig, String> {
+        let provider = Arc::new(ring::default_provider());
+
+        let builder = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|error| format!("Invalid TLS protocol versions: {error}"))?;
+
+        let builder = match Self::read_pem(&self.client_ca_path, &self.client_ca_pem, "client CA")? {
+            Some(client_ca) => {
+                let mut roots = RootCertStore::empty();
+                for cert in CertificateDer::pem_slice_iter(&client_ca) {
+                    let cert = cert.map_err(|error| format!("Invalid TLS client CA: {error}"))?;
+                    roots
+                        .add(cert)
+                        .map_err(|error| format!("Invalid TLS client CA: {error}"))?;
+                }
+
+                let mut verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+                if self.client_auth_optional {
+                    verifier = verifier.allow_unauthenticated();
+                }
+
+                let verifier = verifier
+                    .build()
+                    .map_err(|error| format!("Invalid TLS client verifier: {error}"))?;
+                builder.with_client_cert_verifier(verifier)
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        let mut config = builder.with_cert_resolver(resolver);
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+        Ok(config)
+    }
+}
+
+/// Serves the current certificate, swapped in place on reload.
+#[derive(Debug)]
+struct CertificateResolver {
+    current: RwLock<Arc<CertifiedKey>>,
+}
+
+impl ResolvesServerCert for CertificateResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        self.current.read().ok().map(|current| current.clone())
+    }
+}
+
+/// Connection info of TLS servers, available to handlers as `ConnectInfo<TlsConnectInfo>`.
+#[derive(Debug, Clone)]
+pub struct TlsConnectInfo {
+    remote_addr: SocketAddr,
+    peer: Option<PeerIdentity>,
+}
+
+impl TlsConnectInfo {
+    pub fn remote_addr(&self) -> SocketAddr {
+        self.remote_addr
+    }
+
+    /// Identity of the verified client certificate, when mTLS is enabled.
+    pub fn peer(&self) -> Option<&PeerIdentity> {
+        self.peer.as_ref()
+    }
+}
+
+impl Connected<IncomingStream<'_, TlsListener>> for TlsConnectInfo {
+    fn connect_info(stream: IncomingStream<'_, TlsListener>) -> Self {
+        let peer = stream
+            .io()
+            .get_ref()
+            .1
+            .peer_certificates()
+            .and_then(|certs| certs.first())
+            .and_then(|cert| PeerIdentity::from_der(cert.as_ref()).ok());
+
+        Self {
+            remote_addr: *stream.remote_addr(),
+            peer,
+        }
+    }
+}
+
+/// Subject of the client certificate verified by mTLS.
+///
+/// As an extractor it rejects requests without client certificate with `401 Unauthorized`.
+#[derive(Debug, Clone)]
+pub struct PeerIdentity {
+    common_name: Option<String>,
+    subject: String,
+    subject_alt_names: Vec<String>,
+}
+
+impl PeerIdentity {
+    fn from_der(der: &[u8]) -> Result<Self, String> {
+        let blocks = simple_asn1::from_der(der).map_err(|error| format!("Invalid certificate: {error}"))?;
+        let Some(ASN1Block::Sequence(_, certificate)) = blocks.first() else {
+            return Err("Invalid certificate: missing certificate sequence".to_string());
+        };
+        let Some(ASN1Block::Sequence(_, tbs)) = certificate.first() else {
+            return Err("Invalid certificate: missing TBS certificate".to_string());
+        };
+
+        // the version is an optional [0] field before serial number, signature, issuer, validity and subject
+        let fields = match tbs.first() {
+            Some(block) if is_context_tag(block, 0) => &tbs[1..],
+            _ => &tbs[..],
+        };
+        let Some(ASN1Block::Sequence(_, subject)) = fields.get(4) else {
+            return Err("Invalid certificate: missing subject".to_string());
+        };
+
+        let attributes = subject
+            .iter()
+            .filter_map(|name| match name {
+                ASN1Block::Set(_, attributes) => Some(attributes),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|attribute| match attribute {
+                ASN1Block::Sequence(_, pair) => match pair.as_slice() {
+                    [ASN1Block::ObjectIdentifier(_, oid), value] => Some((oid_values(oid), string_value(value)?)),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let common_name = attributes
+            .iter()
+            .find(|(oid, _)| oid.as_slice() == [2, 5, 4, 3])
+            .map(|(_, value)| value.to_string());
+
+        let subject = attributes
+            .iter()
+            .map(|(oid, value)| format!("{}={value}", attribute_name(oid)))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let subject_alt_names = fields
+            .iter()
+            .find_map(|field| match field {
+                ASN1Block::Explicit(_, _, _, extensions) if is_context_tag(field, 3) => match extensions.as_ref() {
+                    ASN1Block::Sequence(_, extensions) => Some(extensions),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .into_iter()
+            .flatten()
+            .find_map(|extension| match extension {
+                ASN1Block::Sequence(_, extension) => match (extension.first(), extension.last()) {
+                    (Some(ASN1Block::ObjectIdentifier(_, oid)), Some(ASN1Block::OctetString(_, value)))
+                        if oid_values(oid) == [2, 5, 29, 17] =>
+                    {
+                        Some(general_names(value))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            common_name,
+            subject,
+            subject_alt_names,
+        })
+    }
+
+    pub fn common_name(&self) -> Option<&str> {
+        self.common_name.as_deref()
+    }
+
+    /// Full subject, such as `CN=orders,O=acme`.
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// DNS, URI (e.g. SPIFFE ids) and email subject alternative names.
+    pub fn subject_alt_names(&self) -> &[String] {
+        &self.subject_alt_names
+    }
+}
+
+fn is_context_tag(block: &ASN1Block, tag: u8) -> bool {
+    match block {
+        ASN1Block::Explicit(ASN1Class::ContextSpecific, _, block_tag, _)
+        | ASN1Block::Unknown(ASN1Class::ContextSpecific, _, _, block_tag, _) => *block_tag == BigUint::from(tag),
+        _ => false,
+    }
+}
+
+fn oid_values(oid: &OID) -> Vec<u64> {
+    oid.as_vec::<u64>().unwrap_or_default()
+}
+
+fn string_value(block: &ASN1Block) -> Option<&str> {
+    match block {
+        ASN1Block::UTF8String(_, value)
+        | ASN1Block::PrintableString(_, value)
+        | ASN1Block::TeletexString(_, value)
+        | ASN1Block::IA5String(_, value)
+        | ASN1Block::UniversalString(_, value)
+        | ASN1Block::BMPString(_, value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Short names of the usual subject attributes, the others are named by their OID.
+fn attribute_name(oid: &[u64]) -> String {
+    let name = match oid {
+        [2, 5, 4, 3] => "CN",
+        [2, 5, 4, 5] => "serialNumber",
+        [2, 5, 4, 6] => "C",
+        [2, 5, 4, 7] => "L",
+        [2, 5, 4, 8] => "ST",
+        [2, 5, 4, 9] => "street",
+        [2, 5, 4, 10] => "O",
+        [2, 5, 4, 11] => "OU",
+        [0, 9, 2342, 19200300, 100, 1, 1] => "UID",
+        [0, 9, 2342, 19200300, 100, 1, 25] => "DC",
+        [1, 2, 840, 113549, 1, 9, 1] => "emailAddress",
+        _ => {
+            return oid
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join(".")
+        }
+    };
+
+    name.to_string()
+}
+
+/// Email (`[1]`), DNS (`[2]`) and URI (`[6]`) entries of a subject alternative name extension.
+fn general_names(der: &[u8]) -> Vec<String> {
+    match simple_asn1::from_der(der).ok().as_deref() {
+        Some([ASN1Block::Sequence(_, names)]) => names
+            .iter()
+            .filter_map(|name| match name {
+                ASN1Block::Unknown(ASN1Class::ContextSpecific, false, _, _, value)
+                    if [1, 2, 6].into_iter().any(|tag| is_context_tag(name, tag)) =>
+                {
+                    String::from_utf8(value.clone()).ok()
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+impl<S> FromRequestParts<S> for PeerIdentity
+where
+    S: Send + Sync,
+{
+    type Rejection = HttpError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<ConnectInfo<TlsConnectInfo>>()
+            .and_then(|ConnectInfo(info)| info.peer.clone())
+            .ok_or_else(|| {
+                HttpError::without_body(
+                    StatusCode::UNAUTHORIZED,
+                    "Missing client certificate".to_string(),
+                    HttpTags::default(),
+                )
+            })
+    }
+}
+
+/// TCP listener terminating TLS. Handshakes run in their own tasks, so a slow
+/// client never holds back the others.
+pub struct TlsListener {
+    local_addr: SocketAddr,
+    connections: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.connections.recv().await {
+            Some(connection) => connection,
+            // the acceptor only stops on shutdown, when the server stops accepting anyway
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+/// Validated TLS setup, built before the server starts so configuration
+/// errors are returned by `run`.
+pub(crate) struct TlsSetup {
+    config: TlsConfig,
+    acceptor: TlsAcceptor,
+    resolver: Arc<CertificateResolver>,
+}
+
+impl TlsSetup {
+    pub(crate) fn new(config: TlsConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let certified_key = config.certified_key(&ring::default_provider())?;
+        let resolver = Arc::new(CertificateResolver {
+            current: RwLock::new(Arc::new(certified_key)),
+        });
+        let server_config = config.server_config(resolver.clone())?;
+
+        Ok(Self {
+            config,
+            acceptor: TlsAcceptor::from(Arc::new(server_config)),
+            resolver,
+        })
+    }
+
+    /// Starts accepting TLS connections on `listener` until `shutdown` is cancelled.
+    pub(crate) fn listen(self, listener: TcpListener, shutdown: CancellationToken) -> std::io::Result<TlsListener> {
+        let local_addr = listener.local_addr()?;
+        let (sender, connections) = mpsc::channel(ACCEPT_BACKLOG);
+
+        if let Some(interval) = self.config.reload_interval_in_secs {
+            tokio::spawn(reload_certificate(
+                self.config.clone(),
+                self.resolver.clone(),
+                Duration::from_secs(interval),
+                shutdown.clone(),
+            ));
+        }
+
+        tokio::spawn(accept_connections(listener, self.acceptor, sender, shutdown));
+
+        Ok(TlsListener {
+            local_addr,
+            connections,
+        })
+    }
+}
+
+async fn accept_connections(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    sender: mpsc::Sender<(TlsStream<TcpStream>, SocketAddr)>,
+    shutdown: CancellationToken,
+) {
+    loop {
+        let (stream, remote_addr) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(error) => {
+                    error!("Failed to accept TLS connection: {error}");
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            },
+            _ = shutdown.cancelled() => break,
+        };
+
+        let acceptor = acceptor.clone();
+        let sender = sender.clone();
+
+        tokio::spawn(async move {
+            let handshake = tokio::time::timeout(
+                Duration::from_secs(HANDSHAKE_TIMEOUT_IN_SECS),
+                acceptor.accept(stream),
+            );
+
+            match handshake.await {
+                Ok(Ok(stream)) => {
+                    let _ = sender.send((stream, remote_addr)).await;
+                }
+                Ok(Err(error)) => debug!("TLS handshake with {remote_addr} failed: {error}"),
+                Err(_) => debug!("TLS handshake with {remote_addr} timed out"),
+            }
+        });
+    }
+}
+
+async fn reload_certificate(
+    config: TlsConfig,
+    resolver: Arc<CertificateResolver>,
+    interval: Duration,
+    shutdown: CancellationToken,
+) {
+    let provider = ring::default_provider();
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {},
+            _ = shutdown.cancelled() => break,
+        }
+
+        let certified_key = match config.certified_key(&provider) {
+            Ok(certified_key) => certified_key,
+            Err(error) => {
+                error!("Failed to reload TLS certificate, keeping the current one: {error}");
+                continue;
+            }
+        };
+
+        if let Ok(mut current) = resolver.current.write() {
+            if current.cert != certified_key.cert {
+                *current = Arc::new(certified_key);
+                info!("TLS certificate reloaded");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use openssl::asn1::Asn1Time;
+    use openssl::hash::MessageDigest;
+    use openssl::nid::Nid;
+    use openssl::pkey::PKey;
+    use openssl::rsa::Rsa;
+    use openssl::x509::extension::SubjectAlternativeName;
+    use openssl::x509::{X509NameBuilder, X509};
+
+    fn self_signed(common_name: &str) -> (String, String) {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_nid(Nid::COMMONNAME, common_name).unwrap();
+        name.append_entry_by_nid(Nid::ORGANIZATIONNAME, "acme").unwrap();
+        let name = name.build();
+
+        let mut cert = X509::builder().unwrap();
+        cert.set_version(2).unwrap();
+        cert.set_subject_name(&name).unwrap();
+        cert.set_issuer_name(&name).unwrap();
+        cert.set_pubkey(&key).unwrap();
+        cert.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+        cert.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+        let san = SubjectAlternativeName::new()
+            .dns("orders.internal")
+            .uri("spiffe://acme/orders")
+            .email("orders@acme.com")
+            .build(&cert.x509v3_context(None, None))
+            .unwrap();
+        cert.append_extension(san).unwrap();
+        cert.sign(&key, MessageDigest::sha256()).unwrap();
+
+        (
+            String::from_utf8(cert.build().to_pem().unwrap()).unwrap(),
+            String::from_utf8(key.private_key_to_pem_pkcs8().unwrap()).unwrap(),
+        )
+    }
+
+    #[test]
+    fn should_load_inline_certificate_and_key() {
+        let (cert, key) = self_signed("orders");
+        let config = TlsConfig {
+            cert_pem: Some(cert.clone()),
+            key_pem: Some(key),
+            client_ca_pem: Some(cert),
+            ..TlsConfig::default()
+        };
+
+        assert!(TlsSetup::new(config).is_ok());
+    }
+
+    #[test]
+    fn should_fail_without_key() {
+        let (cert, _) = self_signed("orders");
+        let config = TlsConfig {
+            cert_pem: Some(cert),
+            ..TlsConfig::default()
+        };
+
+        let error = TlsSetup::new(config).err().unwrap();
+
+        assert_eq!(error.to_string(), "TLS key is not configured");
+    }
+
+    #[test]
+    fn should_extract_peer_identity() {
+        let (cert, _) = self_signed("orders");
+        let der = CertificateDer::from_pem_slice(cert.as_bytes()).unwrap();
+
+        let peer = PeerIdentity::from_der(der.as_ref()).unwrap();
+
+        assert_eq!(peer.common_name(), Some("orders"));
+        assert_eq!(peer.subject(), "CN=orders,O=acme");
+        assert_eq!(
+            peer.subject_alt_names(),
+            ["orders.internal", "spiffe://acme/orders", "orders@acme.com"]
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_peer_certificates() {
+        assert!(PeerIdentity::from_der(b"not a certificate").is_err());
+    }
+
+    #[tokio::test]
+    async fn should_accept_tls_connections() {
+        let (cert, key) = self_signed("orders");
+        let config = TlsConfig {
+            cert_pem: Some(cert.clone()),
+            key_pem: Some(key),
+            ..TlsConfig::default()
+        };
+
+        let shutdown = CancellationToken::new();
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut listener = TlsSetup::new(config).unwrap().listen(tcp_listener, shutdown.clone()).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let mut roots = RootCertStore::empty();
+        roots.add(CertificateDer::from_pem_slice(cert.as_bytes()).unwrap()).unwrap();
+        let client_config = rustls::ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let connector = tokio_rustls::TlsConnector::from(Arc::new(client_config));
+
+        let client = tokio::spawn(async move {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            connector.connect("orders.internal".try_into().unwrap(), stream).await
+        });
+
+        let (_, remote_addr) = listener.accept().await;
+
+        assert!(client.await.unwrap().is_ok());
+        assert!(remote_addr.ip().is_loopback());
+        shutdown.cancel();
+    }
+}
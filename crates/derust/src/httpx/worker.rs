@@ -0,0 +1,160 @@
+use crate::httpx::{AppContext, HttpError};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
+use crate::metricx::{current_gauge, increment_one, MetricTags};
+
+/// Long-running job driven by the server lifecycle, such as a cron-like job,
+/// a cache warmer or a custom consumer.
+///
+/// Workers are registered through [`ServerBuilder::with_background_worker`](crate::httpx::ServerBuilder::with_background_worker)
+/// and must return once `shutdown` is cancelled. Returning before that, returning
+/// an error or panicking is logged as a failure and counted in the
+/// `background_worker_stopped` metric.
+#[async_trait::async_trait]
+pub trait BackgroundWorker<S>: Send + Sync
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn name(&self) -> &str;
+
+    async fn run(&self, context: AppContext<S>, shutdown: CancellationToken) -> Result<(), HttpError>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum WorkerStopReason {
+    Shutdown,
+    EarlyExit,
+    Error,
+    Panic,
+}
+
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
+impl WorkerStopReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            WorkerStopReason::Shutdown => "shutdown",
+            WorkerStopReason::EarlyExit => "early_exit",
+            WorkerStopReason::Error => "error",
+            WorkerStopReason::Panic => "panic",
+        }
+    }
+}
+
+pub(crate) async fn run_worker<S>(
+    context: AppContext<S>,
+    worker: Arc<dyn BackgroundWorker<S>>,
+    shutdown: CancellationToken,
+) -> WorkerStopReason
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let name = worker.name().to_string();
+
+    info!("Started background worker {name}");
+
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
+    current_gauge(&context, "background_worker_running", worker_metric_tags(&name), 1.0);
+
+    let handle = {
+        let context = context.clone();
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move { worker.run(context, shutdown).await })
+    };
+
+    let reason = match handle.await {
+        Ok(Ok(())) if shutdown.is_cancelled() => {
+            info!("Background worker {name} stopped");
+            WorkerStopReason::Shutdown
+        }
+        Ok(Ok(())) => {
+            error!("Background worker {name} exited before shutdown was requested");
+            WorkerStopReason::EarlyExit
+        }
+        Ok(Err(err)) => {
+            let error_message = context.redaction().redact_text(&err.to_string());
+            error!("Background worker {name} failed: {error_message}");
+            WorkerStopReason::Error
+        }
+        Err(join_error) => {
+            error!("Background worker {name} panicked: {join_error}");
+            WorkerStopReason::Panic
+        }
+    };
+
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
+    {
+        current_gauge(&context, "background_worker_running", worker_metric_tags(&name), 0.0);
+        increment_one(
+            &context,
+            "background_worker_stopped",
+            worker_metric_tags(&name).push("reason".to_string(), reason.as_str().to_string()),
+        );
+    }
+
+    reason
+}
+
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
+fn worker_metric_tags(name: &str) -> MetricTags {
+    MetricTags::from([("worker", name)])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::httpx::test_context;
+    use crate::httpx::HttpTags;
+    use axum::http::StatusCode;
+
+    #[derive(Clone)]
+    struct AppState;
+
+    struct Worker(&'static str);
+
+    #[async_trait::async_trait]
+    impl BackgroundWorker<AppState> for Worker {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        async fn run(
+            &self,
+            _context: AppContext<AppState>,
+            shutdown: CancellationToken,
+        ) -> Result<(), HttpError> {
+            match self.0 {
+                "until_shutdown" => {
+                    shutdown.cancelled().await;
+                    Ok(())
+                }
+                "early_exit" => Ok(()),
+                "error" => Err(HttpError::without_body(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "boom".to_string(),
+                    HttpTags::default(),
+                )),
+                _ => panic!("boom"),
+            }
+        }
+    }
+
+    async fn run(name: &'static str, shutdown: CancellationToken) -> WorkerStopReason {
+        let context = test_context(AppState).await;
+        run_worker(context, Arc::new(Worker(name)), shutdown).await
+    }
+
+    #[tokio::test]
+    async fn should_surface_how_the_worker_stopped() {
+        let shutdown = CancellationToken::new();
+        let until_shutdown = tokio::spawn(run("until_shutdown", shutdown.clone()));
+        shutdown.cancel();
+
+        assert_eq!(until_shutdown.await.unwrap(), WorkerStopReason::Shutdown);
+        assert_eq!(run("early_exit", CancellationToken::new()).await, WorkerStopReason::EarlyExit);
+        assert_eq!(run("error", CancellationToken::new()).await, WorkerStopReason::Error);
+        assert_eq!(run("panic", CancellationToken::new()).await, WorkerStopReason::Panic);
+    }
+}
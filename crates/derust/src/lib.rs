@@ -19,7 +19,7 @@ pub mod outboxx;
 #[cfg(any(feature = "aws", feature = "env_from_secrets_manager"))]
 pub mod awsx;
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 pub mod metricx;
 
 #[cfg(any(feature = "postgres", feature = "outbox"))]
@@ -0,0 +1,199 @@
+use crate::httpx::AppContext;
+use crate::metricx::meters::MetricTags;
+use metrics::Label;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+/// Value reported in place of the tag values beyond `max_tag_values_per_metric`.
+pub const OTHER_TAG_VALUE: &str = "__other__";
+/// Counter incremented, tagged with `metric` and `tag`, whenever a value is collapsed.
+pub const CARDINALITY_EXCEEDED_METRIC: &str = "metric_cardinality_exceeded";
+
+const RESERVED_TAGS: [&str; 2] = ["app_name", "env"];
+
+/// Limits of the metric labels, set through [`AppContext::with_metric_cardinality`],
+/// applied after the denied tags of the registry configs.
+#[derive(Debug, Clone, Default)]
+pub struct CardinalityConfig {
+    /// When set, only these tags become labels, including the ones of the
+    /// derust metrics, such as `method`, `path` and `status`.
+    /// `app_name` and `env` are always kept.
+    pub allowed_metric_tags: Option<Vec<String>>,
+    /// Distinct values kept per metric and tag. Further values are reported as
+    /// [`OTHER_TAG_VALUE`].
+    pub max_tag_values_per_metric: Option<usize>,
+}
+
+/// Tag values seen per metric, shared by the clones of the [`AppContext`].
+#[derive(Clone, Default)]
+pub(crate) struct CardinalityGuard {
+    config: CardinalityConfig,
+    state: Arc<Mutex<GuardState>>,
+}
+
+#[derive(Default)]
+struct GuardState {
+    values: HashMap<(String, String), HashSet<String>>,
+    exceeded: HashSet<(String, String)>,
+}
+
+/// Tag of a metric whose value was collapsed, and whether it's the first time.
+struct Collapsed {
+    tag: String,
+    first: bool,
+}
+
+impl CardinalityGuard {
+    pub(crate) fn new(config: CardinalityConfig) -> Self {
+        Self {
+            config,
+            state: Arc::default(),
+        }
+    }
+
+    pub(crate) fn config(&self) -> &CardinalityConfig {
+        &self.config
+    }
+
+    fn guard(&self, metric_name: &str, labels: Vec<Label>) -> (Vec<Label>, Vec<Collapsed>) {
+        let labels = labels
+            .into_iter()
+            .filter(|label| self.is_allowed(label.key()))
+            .collect::<Vec<_>>();
+
+        let Some(max_values) = self.config.max_tag_values_per_metric else {
+            return (labels, vec![]);
+        };
+
+        let mut collapsed = vec![];
+        let mut state = self.state.lock().unwrap_or_else(|error| error.into_inner());
+
+        let labels = labels
+            .into_iter()
+            .map(|label| {
+                if RESERVED_TAGS.contains(&label.key()) {
+                    return label;
+                }
+
+                let key = (metric_name.to_string(), label.key().to_string());
+                let values = state.values.entry(key.clone()).or_default();
+
+                if values.contains(label.value()) {
+                    return label;
+                }
+                if values.len() < max_values {
+                    values.insert(label.value().to_string());
+                    return label;
+                }
+
+                collapsed.push(Collapsed {
+                    tag: label.key().to_string(),
+                    first: state.exceeded.insert(key),
+                });
+                Label::new(label.key().to_string(), OTHER_TAG_VALUE)
+            })
+            .collect();
+
+        (labels, collapsed)
+    }
+
+    fn is_allowed(&self, tag: &str) -> bool {
+        RESERVED_TAGS.contains(&tag)
+            || self
+                .config
+                .allowed_metric_tags
+                .as_ref()
+                .is_none_or(|allowed| allowed.iter().any(|allowed| allowed == tag))
+    }
+}
+
+/// Labels of a metric, without the denied tags and within the cardinality limits.
+pub(crate) fn metric_labels<S>(context: &AppContext<S>, metric_name: &str, metric_tags: MetricTags) -> Vec<Label>
+where
+    S: Clone,
+{
+    let labels = metric_tags.to_labels(
+        context.app_name(),
+        context.env(),
+        context.denied_metric_tags(),
+        context.denied_metric_tags_by_regex(),
+    );
+
+    let (labels, collapsed) = context.cardinality_guard().guard(metric_name, labels);
+
+    for Collapsed { tag, first } in collapsed {
+        if first {
+            warn!(
+                "Metric {metric_name} exceeded {} distinct values of the tag {tag}, further values are reported as {OTHER_TAG_VALUE}",
+                context.cardinality_guard().config().max_tag_values_per_metric.unwrap_or_default(),
+            );
+        }
+
+        metrics::counter!(
+            CARDINALITY_EXCEEDED_METRIC,
+            MetricTags::from([("metric", metric_name), ("tag", tag.as_str())]).to_labels(
+                context.app_name(),
+                context.env(),
+                &[],
+                &[],
+            )
+        )
+        .increment(1);
+    }
+
+    labels
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn labels_of(values: &[(&str, &str)]) -> Vec<Label> {
+        values
+            .iter()
+            .map(|(key, value)| Label::new(key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn should_keep_only_allowed_tags() {
+        let guard = CardinalityGuard::new(CardinalityConfig {
+            allowed_metric_tags: Some(vec!["status".to_string()]),
+            ..CardinalityConfig::default()
+        });
+
+        let (labels, collapsed) = guard.guard(
+            "orders",
+            labels_of(&[("status", "200"), ("user_id", "42"), ("app_name", "test")]),
+        );
+
+        assert_eq!(labels, labels_of(&[("status", "200"), ("app_name", "test")]));
+        assert!(collapsed.is_empty());
+    }
+
+    #[test]
+    fn should_collapse_values_beyond_the_cap() {
+        let guard = CardinalityGuard::new(CardinalityConfig {
+            max_tag_values_per_metric: Some(2),
+            ..CardinalityConfig::default()
+        });
+
+        for user_id in ["1", "2", "1"] {
+            let (labels, collapsed) = guard.guard("orders", labels_of(&[("user_id", user_id)]));
+            assert_eq!(labels, labels_of(&[("user_id", user_id)]));
+            assert!(collapsed.is_empty());
+        }
+
+        let (labels, collapsed) = guard.guard("orders", labels_of(&[("user_id", "3"), ("env", "test")]));
+        assert_eq!(labels, labels_of(&[("user_id", OTHER_TAG_VALUE), ("env", "test")]));
+        assert!(collapsed[0].first);
+
+        let (_, collapsed) = guard.guard("orders", labels_of(&[("user_id", "4")]));
+        assert!(!collapsed[0].first);
+
+        // the cap is per metric
+        let (labels, _) = guard.guard("payments", labels_of(&[("user_id", "3")]));
+        assert_eq!(labels, labels_of(&[("user_id", "3")]));
+    }
+}
@@ -1,16 +1,11 @@
 use crate::httpx::AppContext;
-use crate::metricx::meters::MetricTags;
+use crate::metricx::meters::{metric_labels, MetricTags};
 
 pub fn increment<S>(context: &AppContext<S>, metric_name: &str, metric_tags: MetricTags, count: u64)
 where
     S: Clone,
 {
-    metrics::counter!(metric_name.to_string(), metric_tags.to_labels(
-        context.app_name(),
-        context.env(),
-        context.denied_metric_tags(),
-        context.denied_metric_tags_by_regex(),
-    )).increment(count);
+    metrics::counter!(metric_name.to_string(), metric_labels(context, metric_name, metric_tags)).increment(count);
 }
 
 pub fn increment_one<S>(context: &AppContext<S>, metric_name: &str, metric_tags: MetricTags)
@@ -1,5 +1,5 @@
 use crate::httpx::AppContext;
-use crate::metricx::meters::MetricTags;
+use crate::metricx::meters::{metric_labels, MetricTags};
 
 pub fn current_gauge<S>(
     context: &AppContext<S>,
@@ -9,10 +9,27 @@ pub fn current_gauge<S>(
 ) where
     S: Clone,
 {
-    metrics::gauge!(metric_name.to_string(), metric_tags.to_labels(
-        context.app_name(),
-        context.env(),
-        context.denied_metric_tags(),
-        context.denied_metric_tags_by_regex(),
-    )).set(value);
+    metrics::gauge!(metric_name.to_string(), metric_labels(context, metric_name, metric_tags)).set(value);
+}
+
+pub fn increment_gauge<S>(
+    context: &AppContext<S>,
+    metric_name: &str,
+    metric_tags: MetricTags,
+    value: f64,
+) where
+    S: Clone,
+{
+    metrics::gauge!(metric_name.to_string(), metric_labels(context, metric_name, metric_tags)).increment(value);
+}
+
+pub fn decrement_gauge<S>(
+    context: &AppContext<S>,
+    metric_name: &str,
+    metric_tags: MetricTags,
+    value: f64,
+) where
+    S: Clone,
+{
+    metrics::gauge!(metric_name.to_string(), metric_labels(context, metric_name, metric_tags)).decrement(value);
 }
@@ -1,13 +1,17 @@
+pub(crate) mod cardinality;
 pub(crate) mod counter;
 pub(crate) mod gauge;
 pub(crate) mod money;
+pub(crate) mod size;
 pub(crate) mod tags;
 pub(crate) mod timer;
 
+pub use cardinality::*;
 pub use counter::*;
 pub use gauge::*;
 use metrics::Histogram;
 pub use money::*;
+pub use size::*;
 pub use tags::*;
 pub use timer::*;
 
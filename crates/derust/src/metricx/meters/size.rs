@@ -0,0 +1,14 @@
+use crate::httpx::AppContext;
+use crate::metricx::meters::{metric_labels, MetricTags};
+
+/// Records a size in bytes. Histograms named `*_bytes` get size buckets, from 64B to 16MiB.
+pub fn record_size<S>(
+    context: &AppContext<S>,
+    metric_name: &str,
+    metric_tags: MetricTags,
+    bytes: u64,
+) where
+    S: Clone,
+{
+    metrics::histogram!(metric_name.to_string(), metric_labels(context, metric_name, metric_tags)).record(bytes as f64);
+}
@@ -1,4 +1,5 @@
 use crate::envx::Environment;
+use crate::httpx::middlewares::request_id::REQUEST_ID_TAG;
 use crate::httpx::HttpTags;
 use axum::http::{Method, Uri};
 use lazy_static::lazy_static;
@@ -52,10 +53,25 @@ impl MetricTags {
     }
 
     pub fn http_server(req_url: &Uri, req_method: &Method) -> MetricTags {
-        let path = normalize_path(req_url.path(), REGEXES_REPLACE.clone());
+        Self::http_server_with_patterns(req_url, req_method, &[])
+    }
+
+    /// Same as [`MetricTags::http_server`], applying the given patterns before the built-in ones.
+    pub fn http_server_with_patterns(
+        req_url: &Uri,
+        req_method: &Method,
+        patterns: &[(Regex, String)],
+    ) -> MetricTags {
+        let regexes = patterns.iter().chain(REGEXES_REPLACE.iter()).cloned().collect();
+        let path = normalize_path(req_url.path(), regexes);
         MetricTags::from([("method", req_method.as_str()), ("path", &path)])
     }
 
+    /// Tags of a request matched by the router, labeled with the route template, such as `/orders/{id}`.
+    pub fn http_server_route(route: &str, req_method: &Method) -> MetricTags {
+        MetricTags::from([("method", req_method.as_str()), ("path", route)])
+    }
+
     pub fn http_client(req_url: &str, req_path: &str, req_method: &str) -> MetricTags {
         let path = normalize_path(req_path, REGEXES_REPLACE.clone());
 
@@ -93,8 +109,11 @@ impl MetricTag {
 impl From<&HttpTags> for MetricTags {
     fn from(value: &HttpTags) -> Self {
         let mut tags = MetricTags::default();
+        // one value per request, never a metric label
         for (key, value) in value.values() {
-            tags = tags.push(key, value);
+            if key != REQUEST_ID_TAG {
+                tags = tags.push(key, value);
+            }
         }
         tags
     }
@@ -103,8 +122,11 @@ impl From<&HttpTags> for MetricTags {
 impl From<HttpTags> for MetricTags {
     fn from(value: HttpTags) -> Self {
         let mut tags = MetricTags::default();
+        // one value per request, never a metric label
         for (key, value) in value.values() {
-            tags = tags.push(key, value);
+            if key != REQUEST_ID_TAG {
+                tags = tags.push(key, value);
+            }
         }
         tags
     }
@@ -140,7 +162,7 @@ impl<const N: usize> From<[(&str, &str); N]> for MetricTags {
     }
 }
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 mod test {
     use crate::envx::Environment;
     use crate::metricx::tags::{normalize_path, REGEXES_REPLACE};
@@ -176,6 +198,22 @@ mod test {
         }
     }
 
+    #[test]
+    fn should_apply_custom_patterns_first() {
+        let patterns = vec![(
+            Regex::new("/[a-z0-9]+(-[a-z0-9]+)+$").unwrap(),
+            "/{slug}".to_string(),
+        )];
+
+        let tags = MetricTags::http_server_with_patterns(
+            &"/products/123-value".parse().unwrap(),
+            &axum::http::Method::GET,
+            &patterns,
+        );
+
+        assert_eq!(tags.vec()[1].value(), "/products/{slug}");
+    }
+
     #[tokio::test]
     async fn should_filter_metric_tags() -> Result<(), Box<dyn std::error::Error>> {
         let tags = MetricTags::from([
@@ -1,5 +1,5 @@
 use crate::httpx::AppContext;
-use crate::metricx::meters::MetricTags;
+use crate::metricx::meters::{metric_labels, MetricTags};
 use std::time::Instant;
 
 pub struct Stopwatch<S>
@@ -55,10 +55,5 @@ pub fn record_duration<S>(
 ) where
     S: Clone,
 {
-    metrics::histogram!(metric_name.to_string(), metric_tags.to_labels(
-        context.app_name(),
-        context.env(),
-        context.denied_metric_tags(),
-        context.denied_metric_tags_by_regex(),
-    )).record(value);
+    metrics::histogram!(metric_name.to_string(), metric_labels(context, metric_name, metric_tags)).record(value);
 }
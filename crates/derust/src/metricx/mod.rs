@@ -1,10 +1,16 @@
 mod meters;
-mod registries;
+pub(crate) mod registries;
 
 pub use meters::*;
 
+#[cfg(any(feature = "prometheus", feature = "otlp_metrics"))]
+pub use registries::MONEY_BUCKETS;
+
 #[cfg(feature = "statsd")]
 pub use registries::statsd::*;
 
 #[cfg(feature = "prometheus")]
 pub use registries::prometheus::*;
+
+#[cfg(feature = "otlp_metrics")]
+pub use registries::otlp::*;
@@ -0,0 +1,64 @@
+use crate::envx::Environment;
+#[cfg(feature = "otlp_metrics")]
+use crate::metricx::{otlp_meter_provider, registries::otlp::otlp_recorder, OtlpMetricsConfig};
+#[cfg(feature = "prometheus")]
+use crate::metricx::{prometheus_recorder, PrometheusConfig};
+#[cfg(feature = "statsd")]
+use crate::metricx::{statsd_recorder, StatsdConfig};
+#[cfg(feature = "prometheus")]
+use metrics_exporter_prometheus::PrometheusHandle;
+use metrics_util::layers::FanoutBuilder;
+#[cfg(feature = "otlp_metrics")]
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use tracing::warn;
+
+/// Registries installed at once behind a single recorder, so every metric
+/// reaches all of them with the same labels.
+pub(crate) struct MetricsRegistries {
+    #[cfg(feature = "prometheus")]
+    pub(crate) prometheus_handle: PrometheusHandle,
+    #[cfg(feature = "otlp_metrics")]
+    pub(crate) meter_provider: SdkMeterProvider,
+}
+
+pub(crate) fn install_registries(
+    #[cfg_attr(not(feature = "otlp_metrics"), allow(unused_variables))] app_name: &str,
+    #[cfg_attr(not(feature = "otlp_metrics"), allow(unused_variables))] env: &Environment,
+    #[cfg(feature = "statsd")] statsd_config: &StatsdConfig,
+    #[cfg(feature = "prometheus")] prometheus_config: &PrometheusConfig,
+    #[cfg(feature = "otlp_metrics")] otlp_metrics_config: &OtlpMetricsConfig,
+) -> Result<MetricsRegistries, Box<dyn std::error::Error>> {
+    let mut fanout = FanoutBuilder::default();
+
+    #[cfg(feature = "statsd")]
+    {
+        fanout = fanout.add_recorder(statsd_recorder(statsd_config)?);
+    }
+
+    #[cfg(feature = "prometheus")]
+    let prometheus_handle = {
+        let recorder = prometheus_recorder(prometheus_config)?;
+        let handle = recorder.handle();
+        fanout = fanout.add_recorder(recorder);
+        handle
+    };
+
+    #[cfg(feature = "otlp_metrics")]
+    let meter_provider = {
+        let provider = otlp_meter_provider(app_name, env, otlp_metrics_config)?;
+        fanout = fanout.add_recorder(otlp_recorder(&provider));
+        provider
+    };
+
+    if metrics::set_global_recorder(fanout.build()).is_err() {
+        warn!("A metrics recorder was already installed, the configured registries won't receive any metric");
+    }
+
+    Ok(MetricsRegistries {
+        #[cfg(feature = "prometheus")]
+        prometheus_handle,
+        #[cfg(feature = "otlp_metrics")]
+        meter_provider,
+    })
+}
+
@@ -1,4 +1,29 @@
+pub(crate) mod fanout;
+#[cfg(feature = "otlp_metrics")]
+pub mod otlp;
 #[cfg(feature = "prometheus")]
 pub mod prometheus;
 #[cfg(feature = "statsd")]
 pub mod statsd;
+
+/// Histogram buckets, in seconds, shared by the registries that aggregate histograms.
+#[cfg(any(feature = "prometheus", feature = "otlp_metrics"))]
+pub(crate) const DEFAULT_BUCKETS: [f64; 13] = [
+    0.010, 0.025, 0.050, 0.075, 0.100, 0.150, 0.200, 0.250, 0.500, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Histogram buckets of the `*_bytes` metrics, from 64B to 16MiB.
+#[cfg(any(feature = "prometheus", feature = "otlp_metrics"))]
+pub(crate) const SIZE_BUCKETS: [f64; 10] = [
+    64.0, 256.0, 1_024.0, 4_096.0, 16_384.0, 65_536.0, 262_144.0, 1_048_576.0, 4_194_304.0, 16_777_216.0,
+];
+
+/// Suffix of the metrics recorded with [`SIZE_BUCKETS`].
+#[cfg(any(feature = "prometheus", feature = "otlp_metrics"))]
+pub(crate) const SIZE_METRIC_SUFFIX: &str = "_bytes";
+
+/// Histogram buckets suited to currency amounts, such as the `record_money` metrics.
+#[cfg(any(feature = "prometheus", feature = "otlp_metrics"))]
+pub const MONEY_BUCKETS: [f64; 14] = [
+    1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1_000.0, 2_500.0, 5_000.0, 10_000.0, 50_000.0, 100_000.0,
+];
@@ -0,0 +1,340 @@
+use crate::envx::Environment;
+use crate::metricx::registries::{DEFAULT_BUCKETS, SIZE_BUCKETS, SIZE_METRIC_SUFFIX};
+use crate::tracex::{blocking_http_client, env_var, OtlpProtocol};
+use metrics::{
+    Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+    SharedString, Unit,
+};
+use opentelemetry::metrics::{Meter, MeterProvider};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{MetricExporter, WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+use opentelemetry_sdk::Resource;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{info, warn};
+
+const DEFAULT_EXPORT_INTERVAL_IN_SECS: u64 = 60;
+const HTTP_METRICS_PATH: &str = "/v1/metrics";
+
+/// Pushes the metricx meters to an OpenTelemetry collector.
+///
+/// Unset values fall back to the standard variables (`OTEL_EXPORTER_OTLP_METRICS_ENDPOINT`,
+/// `OTEL_EXPORTER_OTLP_ENDPOINT`, `OTEL_EXPORTER_OTLP_PROTOCOL`...), which are only read.
+#[derive(Clone, Default)]
+pub struct OtlpMetricsConfig {
+    /// Collector base URL, such as `http://localhost:4318`.
+    pub endpoint: Option<String>,
+    /// Inferred from the endpoint port when unset.
+    pub protocol: Option<OtlpProtocol>,
+    pub export_interval_in_secs: Option<u64>,
+    pub timeout_in_millis: Option<u64>,
+}
+
+impl OtlpMetricsConfig {
+    fn resolved_endpoint(&self) -> Option<String> {
+        self.endpoint
+            .clone()
+            .or_else(|| env_var(&["OTEL_EXPORTER_OTLP_METRICS_ENDPOINT", "OTEL_EXPORTER_OTLP_ENDPOINT"]))
+    }
+
+    fn resolved_protocol(&self, endpoint: &str) -> OtlpProtocol {
+        OtlpProtocol::resolve(
+            self.protocol,
+            &["OTEL_EXPORTER_OTLP_METRICS_PROTOCOL", "OTEL_EXPORTER_OTLP_PROTOCOL"],
+            endpoint,
+        )
+    }
+}
+
+pub fn otlp_metrics_registry(
+    app_name: &str,
+    env: &Environment,
+    config: &OtlpMetricsConfig,
+) -> Result<SdkMeterProvider, Box<dyn std::error::Error>> {
+    let provider = otlp_meter_provider(app_name, env, config)?;
+
+    let _ = metrics::set_global_recorder(otlp_recorder(&provider));
+
+    Ok(provider)
+}
+
+/// Same as [`otlp_metrics_registry`], without installing the recorder.
+pub fn otlp_meter_provider(
+    app_name: &str,
+    env: &Environment,
+    config: &OtlpMetricsConfig,
+) -> Result<SdkMeterProvider, Box<dyn std::error::Error>> {
+    let resource = Resource::builder()
+        .with_service_name(app_name.to_string())
+        .with_attribute(KeyValue::new("deployment.environment", env.get_name()))
+        .build();
+
+    let mut builder = SdkMeterProvider::builder().with_resource(resource);
+
+    match build_exporter(config)? {
+        Some((exporter, endpoint)) => {
+            let interval = config
+                .export_interval_in_secs
+                .unwrap_or(DEFAULT_EXPORT_INTERVAL_IN_SECS);
+            let reader = PeriodicReader::builder(exporter)
+                .with_interval(Duration::from_secs(interval))
+                .build();
+            builder = builder.with_reader(reader);
+
+            info!("OTLP metrics registry configured on {endpoint}");
+        }
+        None => warn!("No OTLP endpoint configured, metrics won't be exported"),
+    }
+
+    Ok(builder.build())
+}
+
+pub(crate) fn otlp_recorder(provider: &SdkMeterProvider) -> OtlpRecorder {
+    OtlpRecorder::new(provider.meter("derust"))
+}
+
+fn build_exporter(
+    config: &OtlpMetricsConfig,
+) -> Result<Option<(MetricExporter, String)>, Box<dyn std::error::Error>> {
+    let Some(endpoint) = config.resolved_endpoint() else {
+        return Ok(None);
+    };
+
+    let timeout = config.timeout_in_millis.map(Duration::from_millis);
+
+    let protocol = config.resolved_protocol(&endpoint);
+    let exporter = match protocol {
+        OtlpProtocol::Http | OtlpProtocol::HttpJson => {
+            let endpoint = if endpoint.ends_with(HTTP_METRICS_PATH) {
+                endpoint.clone()
+            } else {
+                format!("{}{HTTP_METRICS_PATH}", endpoint.trim_end_matches('/'))
+            };
+
+            let mut exporter = MetricExporter::builder()
+                .with_http()
+                .with_protocol(protocol.http_protocol())
+                .with_http_client(blocking_http_client(timeout)?)
+                .with_endpoint(endpoint);
+            if let Some(timeout) = timeout {
+                exporter = exporter.with_timeout(timeout);
+            }
+            exporter.build()?
+        }
+        OtlpProtocol::Grpc => {
+            let mut exporter = MetricExporter::builder()
+                .with_tonic()
+                .with_endpoint(endpoint.clone());
+            if let Some(timeout) = timeout {
+                exporter = exporter.with_timeout(timeout);
+            }
+            exporter.build()?
+        }
+    };
+
+    Ok(Some((exporter, endpoint)))
+}
+
+/// Bridges the `metrics` macros to OpenTelemetry instruments. Handles are kept
+/// by key, since gauges are incremented and counters set from their last value.
+pub(crate) struct OtlpRecorder {
+    meter: Meter,
+    counters: Mutex<HashMap<Key, Arc<OtlpCounter>>>,
+    gauges: Mutex<HashMap<Key, Arc<OtlpGauge>>>,
+    histograms: Mutex<HashMap<Key, Arc<OtlpHistogram>>>,
+}
+
+impl OtlpRecorder {
+    fn new(meter: Meter) -> Self {
+        Self {
+            meter,
+            counters: Mutex::new(HashMap::new()),
+            gauges: Mutex::new(HashMap::new()),
+            histograms: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn counter(&self, key: &Key) -> Arc<OtlpCounter> {
+        let mut counters = self.counters.lock().unwrap_or_else(|error| error.into_inner());
+        counters
+            .entry(key.clone())
+            .or_insert_with(|| {
+                Arc::new(OtlpCounter {
+                    counter: self.meter.u64_counter(key.name().to_string()).build(),
+                    attributes: attributes(key),
+                    value: AtomicU64::new(0),
+                })
+            })
+            .clone()
+    }
+
+    fn gauge(&self, key: &Key) -> Arc<OtlpGauge> {
+        let mut gauges = self.gauges.lock().unwrap_or_else(|error| error.into_inner());
+        gauges
+            .entry(key.clone())
+            .or_insert_with(|| {
+                Arc::new(OtlpGauge {
+                    gauge: self.meter.f64_gauge(key.name().to_string()).build(),
+                    attributes: attributes(key),
+                    value: AtomicU64::new(0f64.to_bits()),
+                })
+            })
+            .clone()
+    }
+
+    fn histogram(&self, key: &Key) -> Arc<OtlpHistogram> {
+        let mut histograms = self.histograms.lock().unwrap_or_else(|error| error.into_inner());
+        histograms
+            .entry(key.clone())
+            .or_insert_with(|| {
+                Arc::new(OtlpHistogram {
+                    histogram: self
+                        .meter
+                        .f64_histogram(key.name().to_string())
+                        .with_boundaries(buckets(key.name()))
+                        .build(),
+                    attributes: attributes(key),
+                })
+            })
+            .clone()
+    }
+}
+
+impl Recorder for OtlpRecorder {
+    fn describe_counter(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_gauge(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_histogram(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.counter(key))
+    }
+
+    fn register_gauge(&self, key: &Key, _metadata: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(self.gauge(key))
+    }
+
+    fn register_histogram(&self, key: &Key, _metadata: &Metadata<'_>) -> Histogram {
+        Histogram::from_arc(self.histogram(key))
+    }
+}
+
+fn buckets(metric_name: &str) -> Vec<f64> {
+    if metric_name.ends_with(SIZE_METRIC_SUFFIX) {
+        SIZE_BUCKETS.to_vec()
+    } else {
+        DEFAULT_BUCKETS.to_vec()
+    }
+}
+
+fn attributes(key: &Key) -> Vec<KeyValue> {
+    key.labels()
+        .map(|label| KeyValue::new(label.key().to_string(), label.value().to_string()))
+        .collect()
+}
+
+struct OtlpCounter {
+    counter: opentelemetry::metrics::Counter<u64>,
+    attributes: Vec<KeyValue>,
+    value: AtomicU64,
+}
+
+impl CounterFn for OtlpCounter {
+    fn increment(&self, value: u64) {
+        self.value.fetch_add(value, Ordering::Relaxed);
+        self.counter.add(value, &self.attributes);
+    }
+
+    fn absolute(&self, value: u64) {
+        let previous = self.value.fetch_max(value, Ordering::Relaxed);
+        if value > previous {
+            self.counter.add(value - previous, &self.attributes);
+        }
+    }
+}
+
+struct OtlpGauge {
+    gauge: opentelemetry::metrics::Gauge<f64>,
+    attributes: Vec<KeyValue>,
+    value: AtomicU64,
+}
+
+impl OtlpGauge {
+    fn update(&self, update: impl Fn(f64) -> f64) {
+        let previous = self
+            .value
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some(update(f64::from_bits(bits)).to_bits())
+            })
+            .unwrap_or_else(|bits| bits);
+
+        self.gauge.record(update(f64::from_bits(previous)), &self.attributes);
+    }
+}
+
+impl GaugeFn for OtlpGauge {
+    fn increment(&self, value: f64) {
+        self.update(|current| current + value);
+    }
+
+    fn decrement(&self, value: f64) {
+        self.update(|current| current - value);
+    }
+
+    fn set(&self, value: f64) {
+        self.update(|_| value);
+    }
+}
+
+struct OtlpHistogram {
+    histogram: opentelemetry::metrics::Histogram<f64>,
+    attributes: Vec<KeyValue>,
+}
+
+impl HistogramFn for OtlpHistogram {
+    fn record(&self, value: f64) {
+        self.histogram.record(value, &self.attributes);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use metrics::Label;
+
+    fn recorder() -> OtlpRecorder {
+        OtlpRecorder::new(SdkMeterProvider::default().meter("test"))
+    }
+
+    #[test]
+    fn should_keep_gauge_values_between_registrations() {
+        let recorder = recorder();
+        let key = Key::from_parts("queue_size", vec![Label::new("env", "test")]);
+
+        recorder.register_gauge(&key, &Metadata::new("test", metrics::Level::INFO, None)).set(10.0);
+        recorder.register_gauge(&key, &Metadata::new("test", metrics::Level::INFO, None)).increment(5.0);
+        recorder.register_gauge(&key, &Metadata::new("test", metrics::Level::INFO, None)).decrement(3.0);
+
+        let gauge = recorder.gauge(&key);
+
+        assert_eq!(f64::from_bits(gauge.value.load(Ordering::Relaxed)), 12.0);
+        assert_eq!(gauge.attributes, vec![KeyValue::new("env", "test")]);
+    }
+
+    #[test]
+    fn should_convert_absolute_counters_to_increments() {
+        let recorder = recorder();
+        let key = Key::from_name("processed");
+        let counter = recorder.counter(&key);
+
+        counter.absolute(10);
+        counter.increment(2);
+        counter.absolute(11);
+
+        assert_eq!(counter.value.load(Ordering::Relaxed), 12);
+    }
+}
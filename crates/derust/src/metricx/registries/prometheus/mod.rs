@@ -1,22 +1,107 @@
-use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
-use regex::Regex;
+use crate::metricx::registries::{DEFAULT_BUCKETS, SIZE_BUCKETS, SIZE_METRIC_SUFFIX};
+pub use metrics_exporter_prometheus::Matcher;
+use metrics_exporter_prometheus::{BuildError, PrometheusBuilder, PrometheusHandle, PrometheusRecorder};
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct PrometheusConfig {
-    pub denied_metric_tags: Vec<String>,
-    pub denied_metric_tags_by_regex: Vec<Regex>,
+    /// Buckets of the histograms without an override, from 10ms to 10s when empty.
+    pub buckets: Vec<f64>,
+    /// Bucket overrides by metric name, prefix or suffix. Full names win over
+    /// prefixes, which win over suffixes. `*_bytes` metrics get size buckets by default,
+    /// while `record_money` metrics need one, such as [`MONEY_BUCKETS`](crate::metricx::MONEY_BUCKETS).
+    pub metric_buckets: Vec<(Matcher, Vec<f64>)>,
+    /// Renders the histograms without an override as summaries with these
+    /// quantiles, such as `[0.5, 0.9, 0.99]`, instead of using `buckets`.
+    pub summary_quantiles: Option<Vec<f64>>,
 }
 
-pub fn prometheus_registry() -> Result<PrometheusHandle, Box<dyn std::error::Error>> {
-    let builder = PrometheusBuilder::new()
-        .set_buckets(&[
-            0.010, 0.025, 0.050, 0.075, 0.100, 0.150, 0.200, 0.250, 0.500, 1.0, 2.5, 5.0, 10.0,
-        ])
-        .map_err(|error| Box::new(error))?;
-
-    let handler = builder
-        .install_recorder()
-        .map_err(|error| Box::new(error))?;
+pub fn prometheus_registry(config: &PrometheusConfig) -> Result<PrometheusHandle, Box<dyn std::error::Error>> {
+    let handler = prometheus_builder(config)?.install_recorder()?;
 
     Ok(handler)
 }
+
+/// Same as [`prometheus_registry`], without installing the recorder.
+pub fn prometheus_recorder(config: &PrometheusConfig) -> Result<PrometheusRecorder, Box<dyn std::error::Error>> {
+    Ok(prometheus_builder(config)?.build_recorder())
+}
+
+fn prometheus_builder(config: &PrometheusConfig) -> Result<PrometheusBuilder, BuildError> {
+    let mut builder = match &config.summary_quantiles {
+        // the exporter only renders summaries when no default buckets are set
+        Some(quantiles) => PrometheusBuilder::new().set_quantiles(quantiles)?,
+        None if config.buckets.is_empty() => PrometheusBuilder::new().set_buckets(&DEFAULT_BUCKETS)?,
+        None => PrometheusBuilder::new().set_buckets(&config.buckets)?,
+    };
+
+    // overridable, since a matcher set again replaces the previous buckets
+    builder = builder.set_buckets_for_metric(Matcher::Suffix(SIZE_METRIC_SUFFIX.to_string()), &SIZE_BUCKETS)?;
+
+    for (matcher, buckets) in &config.metric_buckets {
+        builder = builder.set_buckets_for_metric(matcher.clone(), buckets)?;
+    }
+
+    Ok(builder)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::metricx::MONEY_BUCKETS;
+    use metrics::{Key, Level, Metadata, Recorder};
+
+    fn render(config: &PrometheusConfig, records: &[(&'static str, f64)]) -> String {
+        let recorder = prometheus_builder(config).unwrap().build_recorder();
+        let metadata = Metadata::new("test", Level::INFO, None);
+
+        for (name, value) in records {
+            recorder
+                .register_histogram(&Key::from_static_name(name), &metadata)
+                .record(*value);
+        }
+
+        recorder.handle().render()
+    }
+
+    #[test]
+    fn should_override_buckets_by_metric_prefix() {
+        let config = PrometheusConfig {
+            metric_buckets: vec![(Matcher::Prefix("cart_".to_string()), vec![1.0, 5.0, 10.0])],
+            ..PrometheusConfig::default()
+        };
+
+        let rendered = render(&config, &[("cart_items", 3.0), ("http_server_seconds", 0.2)]);
+
+        assert!(rendered.contains("cart_items_bucket{le=\"5\"} 1"));
+        assert!(!rendered.contains("cart_items_bucket{le=\"0.01\"}"));
+        assert!(rendered.contains("http_server_seconds_bucket{le=\"0.01\"} 0"));
+    }
+
+    #[test]
+    fn should_use_money_buckets_for_configured_money_metrics() {
+        let config = PrometheusConfig {
+            metric_buckets: vec![(Matcher::Full("order_paid".to_string()), MONEY_BUCKETS.to_vec())],
+            ..PrometheusConfig::default()
+        };
+
+        let rendered = render(&config, &[("order_paid", 120.0)]);
+
+        assert!(rendered.contains("order_paid_bucket{le=\"250\"} 1"));
+        assert!(!rendered.contains("order_paid_bucket{le=\"0.01\"}"));
+    }
+
+    #[test]
+    fn should_render_summaries_for_metrics_without_override() {
+        let config = PrometheusConfig {
+            metric_buckets: vec![(Matcher::Suffix("_seconds".to_string()), DEFAULT_BUCKETS.to_vec())],
+            summary_quantiles: Some(vec![0.5, 0.99]),
+            ..PrometheusConfig::default()
+        };
+
+        let rendered = render(&config, &[("order_amount", 120.0), ("http_server_seconds", 0.2)]);
+
+        assert!(rendered.contains("# TYPE order_amount summary"));
+        assert!(rendered.contains("order_amount{quantile=\"0.99\"}"));
+        assert!(rendered.contains("# TYPE http_server_seconds histogram"));
+    }
+}
@@ -1,7 +1,6 @@
 use crate::metricx::meters::MetricTags;
 use metrics::{Level, Recorder};
-use metrics_exporter_statsd::StatsdBuilder;
-use regex::Regex;
+use metrics_exporter_statsd::{StatsdBuilder, StatsdRecorder};
 use tracing::info;
 
 const DEFAULT_HOST: &str = "127.0.0.1";
@@ -17,11 +16,16 @@ pub struct StatsdConfig {
     pub queue_size: Option<usize>,
     pub buffer_size: Option<usize>,
     pub default_tags: MetricTags,
-    pub denied_metric_tags: Vec<String>,
-    pub denied_metric_tags_by_regex: Vec<Regex>,
 }
 
 pub fn statsd_registry(config: &StatsdConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let _ = metrics::set_global_recorder(statsd_recorder(config)?);
+
+    Ok(())
+}
+
+/// Same as [`statsd_registry`], without installing the recorder.
+pub fn statsd_recorder(config: &StatsdConfig) -> Result<StatsdRecorder, Box<dyn std::error::Error>> {
     let host = if config.agent_host.is_empty() || config.agent_host == "localhost" {
         DEFAULT_HOST
     } else {
@@ -44,9 +48,7 @@ pub fn statsd_registry(config: &StatsdConfig) -> Result<(), Box<dyn std::error::
     let h = recorder.register_histogram(&key, &metrics::Metadata::new("any", Level::INFO, None));
     h.record(1.0);
 
-    let _ = metrics::set_global_recorder(recorder);
-
     info!("StatsD registry configured on {}:{}", host, port);
 
-    Ok(())
+    Ok(recorder)
 }
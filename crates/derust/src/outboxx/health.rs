@@ -0,0 +1,82 @@
+use crate::httpx::{AppContext, HealthCheck, HealthIndicator, HttpTags};
+use crate::outboxx::runner::oldest_unprocessed_delay_in_secs;
+use serde_json::json;
+use std::time::Duration;
+use tracing::error;
+
+/// Compares the delay of the oldest unprocessed outbox message against
+/// `max_delay`. A backlog doesn't prevent serving requests, so it is not critical.
+pub(crate) struct OutboxHealthIndicator {
+    max_delay: Duration,
+}
+
+impl OutboxHealthIndicator {
+    pub(crate) fn new(max_delay: Duration) -> Self {
+        Self { max_delay }
+    }
+
+    fn delay_check(&self, delay: i32) -> HealthCheck {
+        let details = json!({
+            "delay_in_secs": delay,
+            "max_delay_in_secs": self.max_delay.as_secs(),
+        });
+
+        if delay.max(0) as u64 > self.max_delay.as_secs() {
+            HealthCheck::failure().with_details(details)
+        } else {
+            HealthCheck::ok().with_details(details)
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> HealthIndicator<S> for OutboxHealthIndicator
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn name(&self) -> &str {
+        "outbox"
+    }
+
+    fn critical(&self) -> bool {
+        false
+    }
+
+    async fn check(&self, context: &AppContext<S>) -> HealthCheck {
+        match oldest_unprocessed_delay_in_secs(context, &HttpTags::default()).await {
+            Ok(delay) => self.delay_check(delay),
+            Err(error) => {
+                // the error may hold the host or the user, so it is only logged
+                error!(
+                    "Health check of outbox failed: {}",
+                    context.redaction().redact_text(&error.to_string())
+                );
+
+                HealthCheck::failure().with_details(json!({ "error": "query failed" }))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::httpx::{test_context, HealthStatus};
+
+    #[tokio::test]
+    async fn should_fail_once_the_delay_exceeds_the_max_delay() {
+        let indicator = OutboxHealthIndicator::new(Duration::from_secs(300));
+
+        assert_eq!(indicator.delay_check(-5).status(), &HealthStatus::Ok);
+        assert_eq!(indicator.delay_check(300).status(), &HealthStatus::Ok);
+        assert_eq!(indicator.delay_check(301).status(), &HealthStatus::Failure);
+        assert!(!HealthIndicator::<()>::critical(&indicator));
+
+        let check = HealthIndicator::<()>::check(&indicator, &test_context(()).await).await;
+        assert_eq!(check.status(), &HealthStatus::Failure);
+        assert_eq!(
+            serde_json::to_value(&check).unwrap()["details"]["error"],
+            "query failed"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+mod health;
 mod http;
 mod runner;
 mod sns;
@@ -6,12 +7,15 @@ mod sqs;
 pub use http::*;
 pub use outbox_pattern_processor::outbox_resources::OutboxProcessorResources;
 pub use runner::*;
+pub(crate) use health::OutboxHealthIndicator;
 
-use crate::httpx::{AppContext, HttpError, HttpTags};
+use crate::httpx::{current_request_id, AppContext, HttpError, HttpTags, REQUEST_ID_HEADER};
 use axum::http::StatusCode;
 use outbox_pattern_processor::outbox::Outbox;
 use outbox_pattern_processor::outbox_repository::OutboxRepository;
+use sqlx::types::Json;
 use sqlx::PgConnection;
+use std::collections::HashMap;
 
 #[cfg(feature = "aws")]
 pub use sqs::*;
@@ -19,25 +23,35 @@ pub use sqs::*;
 #[cfg(feature = "aws")]
 pub use sns::*;
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 use crate::metricx::{timer, MetricTags};
 
 pub async fn insert_outbox<S>(
     context: &AppContext<S>,
     db_conn: &mut PgConnection,
-    outbox: Outbox,
+    mut outbox: Outbox,
     tags: &HttpTags,
 ) -> Result<Outbox, HttpError>
 where
     S: Clone,
 {
-    #[cfg(any(feature = "statsd", feature = "prometheus"))]
+    // sent as an HTTP header or SQS/SNS message attribute
+    if let Some(request_id) = current_request_id() {
+        outbox
+            .headers
+            .get_or_insert_with(|| Json(HashMap::new()))
+            .0
+            .entry(REQUEST_ID_HEADER.to_string())
+            .or_insert(request_id);
+    }
+
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
     let mut metric_tags = MetricTags::from(tags);
-    #[cfg(any(feature = "statsd", feature = "prometheus"))]
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
     {
         metric_tags = metric_tags.push("operation".to_string(), "insert".to_string());
     }
-    #[cfg(any(feature = "statsd", feature = "prometheus"))]
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
     let stopwatch = timer::start_stopwatch(
         context,
         "repository_outbox_seconds",
@@ -52,9 +66,10 @@ where
                 format!("Failed to insert outbox: {error}"),
                 tags.clone(),
             )
+            .with_source(error)
         });
 
-    #[cfg(any(feature = "statsd", feature = "prometheus"))]
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
     {
         let success = match result {
             Ok(_) => "true",
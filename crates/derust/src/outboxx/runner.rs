@@ -1,63 +1,62 @@
 use crate::httpx::{AppContext, HttpError, HttpTags};
 use crate::metricx::{current_gauge, MetricTags, start_stopwatch};
-use crate::shutdown_signal;
-use axum::http::StatusCode;
 use outbox_pattern_processor::error::OutboxPatternProcessorError;
 use outbox_pattern_processor::outbox_processor::OutboxProcessor;
 use outbox_pattern_processor::outbox_resources::OutboxProcessorResources;
 use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info};
-use wg::WaitGroup;
 
 pub async fn run<T>(
-    wg: WaitGroup,
     context: AppContext<T>,
     outbox_processor_resources: OutboxProcessorResources,
-    #[cfg(any(feature = "statsd", feature = "prometheus"))] metrics_monitor_enabled: bool,
-    #[cfg(any(feature = "statsd", feature = "prometheus"))] outbox_metrics_monitor_interval_in_secs: Option<u64>,
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))] metrics_monitor_enabled: bool,
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))] outbox_metrics_monitor_interval_in_secs: Option<u64>,
 ) where
     T: Clone + Send + Sync + 'static,
 {
+    let shutdown = context.shutdown().token();
+    let mut handles = Vec::new();
+
     if outbox_processor_resources.scheduled_clear_locked_partition.unwrap_or(false) {
-        tokio::spawn(run_clear_locked_partition(wg.add(1), outbox_processor_resources.clone()));
+        handles.push(tokio::spawn(run_clear_locked_partition(outbox_processor_resources.clone(), shutdown.clone())));
     }
 
-    #[cfg(any(feature = "statsd", feature = "prometheus"))]
+    #[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
     if metrics_monitor_enabled {
-        tokio::spawn(metrics_monitor(wg.add(1), context.clone(), outbox_processor_resources.clone(), outbox_metrics_monitor_interval_in_secs));
+        handles.push(tokio::spawn(metrics_monitor(context.clone(), outbox_processor_resources.clone(), outbox_metrics_monitor_interval_in_secs)));
     }
 
     info!("Started embedded outbox-pattern-processor");
 
     let _ = OutboxProcessor::new(outbox_processor_resources.clone())
-        .with_graceful_shutdown(shutdown_signal())
+        .with_graceful_shutdown(shutdown.cancelled_owned())
         .init_process()
         .await;
 
-    wg.done();
+    for handle in handles {
+        let _ = handle.await;
+    }
 
     info!("Embedded outbox-pattern-processor stopped");
 }
 
 async fn run_clear_locked_partition(
-    wg: WaitGroup,
     outbox_processor_resources: OutboxProcessorResources,
+    shutdown: CancellationToken,
 ) {
     info!("Started embedded outbox-pattern-processor clear locked partition");
 
     let _ = OutboxProcessor::new(outbox_processor_resources)
-        .with_graceful_shutdown(shutdown_signal())
+        .with_graceful_shutdown(shutdown.cancelled_owned())
         .init_processed_locked_cleaner()
         .await;
 
-    wg.done();
-
     info!("Embedded outbox-pattern-processor clear locked partition stopped");
 }
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 async fn metrics_monitor<T>(
-    wg: WaitGroup,
     context: AppContext<T>,
     outbox_processor_resources: OutboxProcessorResources,
     outbox_metrics_monitor_interval_in_secs: Option<u64>,
@@ -66,25 +65,23 @@ async fn metrics_monitor<T>(
 {
     info!("Started embedded outbox-pattern-processor-monitor");
 
-    let mut shutdown_signal = Box::into_pin(Box::new(shutdown_signal()));
+    let shutdown = context.shutdown().token();
 
     loop {
         tokio::select! {
             _ = one_shot_metrics_monitor(&context) => {
                 tokio::time::sleep(Duration::from_secs(outbox_metrics_monitor_interval_in_secs.unwrap_or(5))).await; // TODO ajuste na duration
             }
-            _ = &mut shutdown_signal => {
+            _ = shutdown.cancelled() => {
                 break;
             }
         }
     }
 
-    wg.done();
-
     info!("Embedded outbox-pattern-processor-monitor stopped");
 }
 
-#[cfg(any(feature = "statsd", feature = "prometheus"))]
+#[cfg(any(feature = "statsd", feature = "prometheus", feature = "otlp_metrics"))]
 async fn one_shot_metrics_monitor<T>(context: &AppContext<T>) -> Result<(), HttpError>
 where
     T: Clone + Send + Sync + 'static,
@@ -94,23 +91,7 @@ where
     let mut metric_tags = MetricTags::from(tags.clone()).push("operation".to_string(), "monitor".to_string());
     let stopwatch = start_stopwatch(&context, "outbox_pattern_processor", metric_tags.clone());
 
-    let query = sqlx::query_scalar::<_, i32>(
-        r#"
-        select coalesce(floor(extract(epoch from (now() - min(process_after)))), 0)::int as delay
-        from outbox
-        where processed_at is null
-        "#,
-    );
-
-    let mut conn = context.database().get_connection(true, &tags).await?;
-
-    let result = query.fetch_one(&mut *conn).await.map_err(|error| {
-        HttpError::without_body(
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to one_shot_metrics_monitor: {error}"),
-            tags.clone(),
-        )
-    });
+    let result = oldest_unprocessed_delay_in_secs(context, &tags).await;
 
     match result {
         Ok(delay) => {
@@ -125,3 +106,26 @@ where
 
     Ok(())
 }
+
+/// Seconds since the oldest unprocessed outbox message should have been processed.
+pub(crate) async fn oldest_unprocessed_delay_in_secs<T>(context: &AppContext<T>, tags: &HttpTags) -> Result<i32, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let query = sqlx::query_scalar::<_, i32>(
+        r#"
+        select coalesce(floor(extract(epoch from (now() - min(process_after)))), 0)::int as delay
+        from outbox
+        where processed_at is null
+        "#,
+    );
+
+    let mut conn = context.database().get_connection(true, tags).await?;
+
+    query.fetch_one(&mut *conn).await.map_err(|error| {
+        context
+            .database()
+            .error_status()
+            .http_error(format!("Failed to get the outbox delay: {error}"), error, tags)
+    })
+}
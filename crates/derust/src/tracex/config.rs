@@ -0,0 +1,229 @@
+use crate::envx::Environment;
+use crate::tracex::LogFormat;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const DEFAULT_LOG_LEVEL: &str = "info";
+const DERUST_LOG_DIRECTIVES: &str = "derust=info,tower_http::trace=off,otel::tracing=trace,otel=debug";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SamplingStrategy {
+    AlwaysOn,
+    AlwaysOff,
+    /// Samples `sampling_ratio` of the traces, ignoring the caller decision.
+    Ratio,
+    /// Follows the caller decision, sampling `sampling_ratio` of the traces started here.
+    ParentBasedRatio,
+    /// Follows the caller decision, sampling every trace started here.
+    ParentBasedAlwaysOn,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OtlpProtocol {
+    /// OTLP over HTTP with protobuf payloads, usually on port 4318.
+    Http,
+    /// OTLP over HTTP with JSON payloads.
+    HttpJson,
+    /// OTLP over gRPC, usually on port 4317.
+    Grpc,
+}
+
+impl OtlpProtocol {
+    /// Resolves the protocol of an exporter: the configured one, then the first
+    /// set variable of `env_names`, then the endpoint port.
+    pub(crate) fn resolve(configured: Option<OtlpProtocol>, env_names: &[&str], endpoint: &str) -> OtlpProtocol {
+        if let Some(protocol) = configured {
+            return protocol;
+        }
+
+        match env_var(env_names).as_deref() {
+            Some("grpc") => OtlpProtocol::Grpc,
+            Some("http/protobuf") => OtlpProtocol::Http,
+            Some("http/json") => OtlpProtocol::HttpJson,
+            Some(protocol) => {
+                let inferred = OtlpProtocol::from_endpoint(endpoint);
+                tracing::warn!("Unknown OTLP protocol {protocol}, using {inferred:?} instead");
+                inferred
+            }
+            None => OtlpProtocol::from_endpoint(endpoint),
+        }
+    }
+
+    fn from_endpoint(endpoint: &str) -> OtlpProtocol {
+        if endpoint.contains(":4317") {
+            OtlpProtocol::Grpc
+        } else {
+            OtlpProtocol::Http
+        }
+    }
+
+    /// Payload encoding of the HTTP exporters.
+    pub(crate) fn http_protocol(self) -> opentelemetry_otlp::Protocol {
+        match self {
+            OtlpProtocol::HttpJson => opentelemetry_otlp::Protocol::HttpJson,
+            _ => opentelemetry_otlp::Protocol::HttpBinary,
+        }
+    }
+}
+
+/// Settings of [`tracex::init_with_config`](crate::tracex::init_with_config),
+/// loadable through `envx::load_app_config`.
+///
+/// Unset values fall back to the standard variables (`OTEL_SERVICE_NAME`,
+/// `OTEL_EXPORTER_OTLP_ENDPOINT`, `OTEL_EXPORTER_OTLP_PROTOCOL`, `RUST_LOG`,
+/// `ENVIRONMENT`...), which are only read, never changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TracingConfig {
+    /// `service.name` resource attribute and `app_name` of JSON logs.
+    pub service_name: Option<String>,
+    pub service_version: Option<String>,
+    /// `deployment.environment` resource attribute, also selecting the log format.
+    pub environment: Option<Environment>,
+    /// Extra resource attributes, such as `team` or `k8s.cluster.name`.
+    pub resource_attributes: HashMap<String, String>,
+    /// Sampling strategy, from `OTEL_TRACES_SAMPLER` and `OTEL_TRACES_SAMPLER_ARG`
+    /// when unset, or `parent_based_always_on` without them.
+    pub sampler: Option<SamplingStrategy>,
+    /// Fraction of the traces kept by the ratio strategies, from 0.0 to 1.0.
+    pub sampling_ratio: f64,
+    /// Protocol of the exporter, inferred from the endpoint port when unset.
+    pub exporter_protocol: Option<OtlpProtocol>,
+    /// Collector base URL, such as `http://localhost:4318`. No spans are
+    /// exported when neither this nor `OTEL_EXPORTER_OTLP_ENDPOINT` is set.
+    pub exporter_endpoint: Option<String>,
+    pub exporter_timeout_in_millis: Option<u64>,
+    pub batch_max_queue_size: Option<usize>,
+    pub batch_max_export_batch_size: Option<usize>,
+    pub batch_scheduled_delay_in_millis: Option<u64>,
+    /// Log filter directives, such as `info,sqlx=warn`.
+    pub log_directives: Option<String>,
+    /// Overrides the format chosen from the environment.
+    pub log_format: Option<LogFormat>,
+}
+
+impl Default for TracingConfig {
+    fn default() -> Self {
+        Self {
+            service_name: None,
+            service_version: None,
+            environment: None,
+            resource_attributes: HashMap::new(),
+            sampler: None,
+            sampling_ratio: 1.0,
+            exporter_protocol: None,
+            exporter_endpoint: None,
+            exporter_timeout_in_millis: None,
+            batch_max_queue_size: None,
+            batch_max_export_batch_size: None,
+            batch_scheduled_delay_in_millis: None,
+            log_directives: None,
+            log_format: None,
+        }
+    }
+}
+
+impl TracingConfig {
+    pub fn new(service_name: &str, environment: Environment) -> Self {
+        Self {
+            service_name: Some(service_name.to_string()),
+            environment: Some(environment),
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn resolved_service_name(&self) -> String {
+        self.service_name
+            .clone()
+            .or_else(|| env_var(&["OTEL_SERVICE_NAME", "SERVICE_NAME", "APP_NAME"]))
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn resolved_environment(&self) -> Environment {
+        self.environment
+            .or_else(|| Environment::detect().ok())
+            .unwrap_or(Environment::Local)
+    }
+
+    pub(crate) fn resolved_log_format(&self) -> LogFormat {
+        self.log_format
+            .unwrap_or_else(|| LogFormat::for_env(&self.resolved_environment()))
+    }
+
+    pub(crate) fn resolved_endpoint(&self) -> Option<String> {
+        self.exporter_endpoint
+            .clone()
+            .or_else(|| env_var(&["OTEL_EXPORTER_OTLP_TRACES_ENDPOINT", "OTEL_EXPORTER_OTLP_ENDPOINT"]))
+    }
+
+    pub(crate) fn resolved_protocol(&self, endpoint: &str) -> OtlpProtocol {
+        OtlpProtocol::resolve(
+            self.exporter_protocol,
+            &["OTEL_EXPORTER_OTLP_TRACES_PROTOCOL", "OTEL_EXPORTER_OTLP_PROTOCOL"],
+            endpoint,
+        )
+    }
+
+    /// derust defaults first, so the configured directives can override them.
+    pub(crate) fn resolved_log_directives(&self) -> String {
+        let directives = self
+            .log_directives
+            .clone()
+            .or_else(|| env_var(&["RUST_LOG", "OTEL_LOG_LEVEL"]))
+            .unwrap_or_else(|| DEFAULT_LOG_LEVEL.to_string());
+
+        format!("{DERUST_LOG_DIRECTIVES},{directives}")
+    }
+}
+
+pub(crate) fn env_var(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_prefer_configured_values() {
+        let config = TracingConfig {
+            exporter_endpoint: Some("http://collector:4317".to_string()),
+            log_directives: Some("warn,sqlx=error".to_string()),
+            ..TracingConfig::new("orders", Environment::Production)
+        };
+
+        assert_eq!(config.resolved_service_name(), "orders");
+        assert_eq!(config.resolved_log_format(), LogFormat::Json);
+        assert_eq!(config.resolved_protocol("http://collector:4317"), OtlpProtocol::Grpc);
+        assert!(config.resolved_log_directives().ends_with(",warn,sqlx=error"));
+    }
+
+    #[test]
+    fn should_resolve_the_protocol_from_the_spec_values() {
+        let resolve = |name: &str, value: &str, endpoint: &str| {
+            std::env::set_var(name, value);
+            let protocol = OtlpProtocol::resolve(None, &[name], endpoint);
+            std::env::remove_var(name);
+            protocol
+        };
+
+        assert_eq!(resolve("DERUST_TEST_PROTOCOL_GRPC", "grpc", "http://collector:4318"), OtlpProtocol::Grpc);
+        assert_eq!(
+            resolve("DERUST_TEST_PROTOCOL_PROTOBUF", "http/protobuf", "http://collector:4317"),
+            OtlpProtocol::Http
+        );
+        assert_eq!(
+            resolve("DERUST_TEST_PROTOCOL_JSON", "http/json", "http://collector:4318"),
+            OtlpProtocol::HttpJson
+        );
+        assert_eq!(resolve("DERUST_TEST_PROTOCOL_TYPO", "grcp", "http://collector:4317"), OtlpProtocol::Grpc);
+        assert_eq!(
+            OtlpProtocol::resolve(Some(OtlpProtocol::Http), &["DERUST_TEST_PROTOCOL_UNSET"], "http://collector:4317"),
+            OtlpProtocol::Http
+        );
+    }
+}
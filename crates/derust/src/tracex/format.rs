@@ -0,0 +1,230 @@
+use crate::envx::Environment;
+use axum_tracing_opentelemetry::tracing_opentelemetry_instrumentation_sdk::{
+    find_current_context, find_span_id, find_trace_id,
+};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Display, Formatter};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Field whose entries become top-level keys of JSON logs.
+pub(crate) const TAGS_FIELD: &str = "tags";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Human readable lines.
+    Pretty,
+    /// One JSON object per line, with trace correlation.
+    Json,
+}
+
+impl LogFormat {
+    /// Pretty when running locally, JSON anywhere else.
+    pub fn for_env(env: &Environment) -> Self {
+        if env.is_local() {
+            LogFormat::Pretty
+        } else {
+            LogFormat::Json
+        }
+    }
+}
+
+/// Logs a map as a JSON object, so the JSON formatter can keep it structured.
+pub(crate) struct JsonFields<'a>(pub &'a HashMap<String, String>);
+
+impl Display for JsonFields<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sorted = self.0.iter().collect::<BTreeMap<_, _>>();
+        write!(f, "{}", serde_json::to_string(&sorted).unwrap_or_default())
+    }
+}
+
+pub(crate) struct JsonFormatter {
+    app_name: String,
+    env: String,
+}
+
+impl JsonFormatter {
+    pub(crate) fn new(app_name: &str, env: &Environment) -> Self {
+        Self {
+            app_name: app_name.to_string(),
+            env: env.get_name(),
+        }
+    }
+
+    fn to_json(&self, event: &Event<'_>, trace_id: Option<String>, span_id: Option<String>) -> Map<String, Value> {
+        let metadata = event.metadata();
+        let mut json = Map::new();
+
+        json.insert(
+            "timestamp".to_string(),
+            Value::from(Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)),
+        );
+        json.insert("level".to_string(), Value::from(metadata.level().as_str()));
+        json.insert("target".to_string(), Value::from(metadata.target()));
+
+        if let Some(trace_id) = trace_id {
+            json.insert("trace_id".to_string(), Value::from(trace_id));
+        }
+        if let Some(span_id) = span_id {
+            json.insert("span_id".to_string(), Value::from(span_id));
+        }
+        if !self.app_name.is_empty() {
+            json.insert("app_name".to_string(), Value::from(self.app_name.clone()));
+        }
+        json.insert("env".to_string(), Value::from(self.env.clone()));
+
+        let mut visitor = JsonVisitor::default();
+        event.record(&mut visitor);
+
+        json.insert(
+            "message".to_string(),
+            Value::from(visitor.message.unwrap_or_default()),
+        );
+
+        // tags never replace the keys above
+        for (key, value) in visitor.tags.into_iter().chain(visitor.fields) {
+            json.entry(key).or_insert(value);
+        }
+
+        json
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for JsonFormatter
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result {
+        let context = find_current_context();
+        let json = self.to_json(event, find_trace_id(&context), find_span_id(&context));
+
+        writeln!(writer, "{}", Value::Object(json))
+    }
+}
+
+#[derive(Default)]
+struct JsonVisitor {
+    message: Option<String>,
+    tags: Map<String, Value>,
+    fields: Map<String, Value>,
+}
+
+impl JsonVisitor {
+    fn insert(&mut self, field: &Field, value: Value) {
+        self.fields.insert(field.name().to_string(), value);
+    }
+}
+
+impl Visit for JsonVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_string());
+        } else {
+            self.insert(field, Value::from(value));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        let value = format!("{value:?}");
+
+        match field.name() {
+            "message" => self.message = Some(value),
+            name => match serde_json::from_str::<Value>(&value) {
+                Ok(Value::Object(object)) if name == TAGS_FIELD => self.tags.extend(object),
+                Ok(object @ Value::Object(_)) => self.insert(field, object),
+                _ => self.insert(field, Value::from(value)),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::fmt::MakeWriter;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Output {
+        type Writer = Output;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn should_log_one_json_object_with_tags_at_the_top_level() {
+        let output = Output::default();
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .event_format(JsonFormatter::new("orders", &Environment::Production))
+                .with_writer(output.clone()),
+        );
+
+        let tags = HashMap::from([
+            ("customer_id".to_string(), "42".to_string()),
+            ("message".to_string(), "ignored".to_string()),
+        ]);
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(tags = %JsonFields(&tags), "Order created");
+        });
+
+        let line = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let json: Value = serde_json::from_str(line.trim_end()).unwrap();
+
+        assert_eq!(line.lines().count(), 1);
+        assert_eq!(json["message"], "Order created");
+        assert_eq!(json["level"], "INFO");
+        assert_eq!(json["app_name"], "orders");
+        assert_eq!(json["env"], "production");
+        assert_eq!(json["customer_id"], "42");
+        assert!(json["timestamp"].is_string());
+        assert!(json.get("tags").is_none());
+    }
+}
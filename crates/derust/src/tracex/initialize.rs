@@ -1,35 +1,222 @@
-use init_tracing_opentelemetry::tracing_subscriber_ext::{build_otel_layer, TracingGuard};
+use crate::envx::Environment;
+use crate::tracex::config::{OtlpProtocol, SamplingStrategy, TracingConfig};
+use crate::tracex::format::{JsonFormatter, LogFormat};
+use init_tracing_opentelemetry::init_propagator;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::trace::{
+    BatchConfigBuilder, BatchSpanProcessor, Sampler, SdkTracerProvider,
+};
+use opentelemetry_sdk::Resource;
+use std::time::Duration;
+use tracing::level_filters::LevelFilter;
+use tracing::Subscriber;
 use tracing_subscriber::layer::SubscriberExt;
-use tracing_subscriber::{fmt, EnvFilter};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{fmt, EnvFilter, Layer};
 
+const HTTP_TRACES_PATH: &str = "/v1/traces";
+
+/// Flushes and shuts the tracer provider down when dropped, so the last spans are exported.
+#[must_use = "Hold it with `let _guard = ` until the application stops"]
+pub struct TracingGuard {
+    tracer_provider: SdkTracerProvider,
+}
+
+impl TracingGuard {
+    pub fn tracer_provider(&self) -> &SdkTracerProvider {
+        &self.tracer_provider
+    }
+}
+
+impl Drop for TracingGuard {
+    fn drop(&mut self) {
+        let _ = self.tracer_provider.force_flush();
+        let _ = self.tracer_provider.shutdown();
+    }
+}
+
+/// Same as [`init_with_config`], with every setting read from the standard variables.
 pub fn init() -> Result<TracingGuard, Box<dyn std::error::Error>> {
+    init_with_config(&TracingConfig::default())
+}
+
+/// Same as [`init_with_config`], naming the service and its environment.
+pub fn init_with(app_name: &str, env: &Environment) -> Result<TracingGuard, Box<dyn std::error::Error>> {
+    init_with_config(&TracingConfig::new(app_name, *env))
+}
+
+/// Installs the log and OpenTelemetry subscribers.
+pub fn init_with_config(config: &TracingConfig) -> Result<TracingGuard, Box<dyn std::error::Error>> {
+    let app_name = config.resolved_service_name();
+    let env = config.resolved_environment();
+    let format = config.resolved_log_format();
+
+    // logs the setup itself
     let subscriber = tracing_subscriber::registry()
-        .with(build_loglevel_filter_layer())
-        .with(fmt::layer());
+        .with(build_loglevel_filter_layer(config))
+        .with(build_fmt_layer(format, &app_name, &env));
 
     let _guard = tracing::subscriber::set_default(subscriber);
 
-    let (layer, guard) = build_otel_layer()?;
+    let tracer_provider = build_tracer_provider(config, &app_name, &env)?;
+    init_propagator()?;
+
+    let layer = tracing_opentelemetry::layer()
+        .with_error_records_to_exceptions(true)
+        .with_tracer(tracer_provider.tracer("derust"));
+    global::set_tracer_provider(tracer_provider.clone());
 
     let subscriber = tracing_subscriber::registry()
         .with(layer)
-        .with(build_loglevel_filter_layer())
-        .with(fmt::layer());
+        .with(build_loglevel_filter_layer(config))
+        .with(build_fmt_layer(format, &app_name, &env));
 
     tracing::subscriber::set_global_default(subscriber)?;
 
-    Ok(guard)
+    Ok(TracingGuard { tracer_provider })
+}
+
+fn build_tracer_provider(
+    config: &TracingConfig,
+    app_name: &str,
+    env: &Environment,
+) -> Result<SdkTracerProvider, Box<dyn std::error::Error>> {
+    let mut attributes = vec![KeyValue::new("deployment.environment", env.get_name())];
+    if let Some(version) = &config.service_version {
+        attributes.push(KeyValue::new("service.version", version.clone()));
+    }
+    for (key, value) in &config.resource_attributes {
+        attributes.push(KeyValue::new(key.clone(), value.clone()));
+    }
+
+    let mut resource = Resource::builder().with_attributes(attributes);
+    if !app_name.is_empty() {
+        resource = resource.with_service_name(app_name.to_string());
+    }
+
+    let mut builder = SdkTracerProvider::builder().with_resource(resource.build());
+    // without a sampler the SDK reads the OTEL_TRACES_SAMPLER variables
+    if let Some(sampler) = build_sampler(config) {
+        builder = builder.with_sampler(sampler);
+    }
+
+    match build_exporter(config)? {
+        Some(exporter) => {
+            let processor = BatchSpanProcessor::builder(exporter)
+                .with_batch_config(build_batch_config(config))
+                .build();
+            builder = builder.with_span_processor(processor);
+        }
+        None => tracing::warn!("No OTLP endpoint configured, spans won't be exported"),
+    }
+
+    Ok(builder.build())
+}
+
+fn build_exporter(config: &TracingConfig) -> Result<Option<SpanExporter>, Box<dyn std::error::Error>> {
+    let Some(endpoint) = config.resolved_endpoint() else {
+        return Ok(None);
+    };
+
+    let timeout = config.exporter_timeout_in_millis.map(Duration::from_millis);
+
+    let protocol = config.resolved_protocol(&endpoint);
+    let exporter = match protocol {
+        OtlpProtocol::Http | OtlpProtocol::HttpJson => {
+            let endpoint = if endpoint.ends_with(HTTP_TRACES_PATH) {
+                endpoint
+            } else {
+                format!("{}{HTTP_TRACES_PATH}", endpoint.trim_end_matches('/'))
+            };
+
+            let client = blocking_http_client(timeout)?;
+
+            let mut exporter = SpanExporter::builder()
+                .with_http()
+                .with_protocol(protocol.http_protocol())
+                .with_http_client(client)
+                .with_endpoint(endpoint);
+            if let Some(timeout) = timeout {
+                exporter = exporter.with_timeout(timeout);
+            }
+            exporter.build()?
+        }
+        OtlpProtocol::Grpc => {
+            let mut exporter = SpanExporter::builder().with_tonic().with_endpoint(endpoint);
+            if let Some(timeout) = timeout {
+                exporter = exporter.with_timeout(timeout);
+            }
+            exporter.build()?
+        }
+    };
+
+    Ok(Some(exporter))
+}
+
+/// HTTP client of the OTLP exporters, which export from their own thread, out of any Tokio runtime.
+pub(crate) fn blocking_http_client(
+    timeout: Option<Duration>,
+) -> Result<reqwest::blocking::Client, Box<dyn std::error::Error>> {
+    // the blocking client can't be built inside a Tokio runtime either
+    let client = std::thread::spawn(move || {
+        let mut client = reqwest::blocking::Client::builder();
+        if let Some(timeout) = timeout {
+            client = client.timeout(timeout);
+        }
+        client.build()
+    })
+    .join()
+    .map_err(|_| "Failed to build the OTLP HTTP client")??;
+
+    Ok(client)
+}
+
+fn build_sampler(config: &TracingConfig) -> Option<Sampler> {
+    let sampler = match config.sampler? {
+        SamplingStrategy::AlwaysOn => Sampler::AlwaysOn,
+        SamplingStrategy::AlwaysOff => Sampler::AlwaysOff,
+        SamplingStrategy::Ratio => Sampler::TraceIdRatioBased(config.sampling_ratio),
+        SamplingStrategy::ParentBasedRatio => {
+            Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(config.sampling_ratio)))
+        }
+        SamplingStrategy::ParentBasedAlwaysOn => Sampler::ParentBased(Box::new(Sampler::AlwaysOn)),
+    };
+
+    Some(sampler)
+}
+
+fn build_batch_config(config: &TracingConfig) -> opentelemetry_sdk::trace::BatchConfig {
+    let mut batch = BatchConfigBuilder::default();
+
+    if let Some(max_queue_size) = config.batch_max_queue_size {
+        batch = batch.with_max_queue_size(max_queue_size);
+    }
+    if let Some(max_export_batch_size) = config.batch_max_export_batch_size {
+        batch = batch.with_max_export_batch_size(max_export_batch_size);
+    }
+    if let Some(delay) = config.batch_scheduled_delay_in_millis {
+        batch = batch.with_scheduled_delay(Duration::from_millis(delay));
+    }
+
+    batch.build()
+}
+
+fn build_fmt_layer<S>(format: LogFormat, app_name: &str, env: &Environment) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    match format {
+        LogFormat::Pretty => fmt::layer().boxed(),
+        LogFormat::Json => fmt::layer()
+            .event_format(JsonFormatter::new(app_name, env))
+            .boxed(),
+    }
 }
 
-fn build_loglevel_filter_layer() -> EnvFilter {
-    std::env::set_var(
-        "RUST_LOG",
-        format!(
-            "{},derust=info,tower_http::trace=off,otel::tracing=trace,otel=debug",
-            std::env::var("RUST_LOG")
-                .or_else(|_| std::env::var("OTEL_LOG_LEVEL"))
-                .unwrap_or_else(|_| "info".to_string())
-        ),
-    );
-    EnvFilter::from_default_env()
+fn build_loglevel_filter_layer(config: &TracingConfig) -> EnvFilter {
+    EnvFilter::builder()
+        .with_default_directive(LevelFilter::INFO.into())
+        .parse_lossy(config.resolved_log_directives())
 }
@@ -1,22 +1,23 @@
 use crate::httpx::HttpTags;
+use crate::tracex::format::JsonFields;
 use tracing::{debug, error, info, trace, warn};
 
 pub fn trace(message: &str, tags: &HttpTags) {
-    trace!(tags = ?tags.values(), message);
+    trace!(tags = %JsonFields(&tags.values()), message);
 }
 
 pub fn debug(message: &str, tags: &HttpTags) {
-    debug!(tags = ?tags.values(), message);
+    debug!(tags = %JsonFields(&tags.values()), message);
 }
 
 pub fn info(message: &str, tags: &HttpTags) {
-    info!(tags = ?tags.values(), message);
+    info!(tags = %JsonFields(&tags.values()), message);
 }
 
 pub fn warn(message: &str, tags: &HttpTags) {
-    warn!(tags = ?tags.values(), message);
+    warn!(tags = %JsonFields(&tags.values()), message);
 }
 
 pub fn error(message: &str, tags: &HttpTags) {
-    error!(tags = ?tags.values(), message);
+    error!(tags = %JsonFields(&tags.values()), message);
 }
@@ -1,4 +1,11 @@
+mod config;
+mod format;
 mod initialize;
 pub mod log;
 
+pub use config::{OtlpProtocol, SamplingStrategy, TracingConfig};
+#[cfg(feature = "otlp_metrics")]
+pub(crate) use config::env_var;
+pub use format::LogFormat;
+pub(crate) use format::JsonFields;
 pub use initialize::*;
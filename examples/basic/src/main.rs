@@ -4,7 +4,7 @@ use axum::routing::get;
 use axum::Router;
 use derust::envx::Environment;
 use derust::httpx::json::JsonResponse;
-use derust::httpx::{start, AppContext, HttpError, HttpTags};
+use derust::httpx::{AppContext, HttpError, HttpTags, Server};
 
 #[derive(Clone)]
 pub struct AppState {
@@ -30,7 +30,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // automatic health-check route
     // automatic route response status code log
-    start(port, context, router).await
+    Server::builder(context, router).port(port).run().await
 }
 
 #[derive(serde::Serialize)]
@@ -6,7 +6,7 @@ use sqlx::query_scalar;
 use derust::databasex::{DatabaseConfig, PostgresDatabase, Repository};
 use derust::envx::Environment;
 use derust::httpx::json::JsonResponse;
-use derust::httpx::{start, AppContext, HttpError, HttpTags};
+use derust::httpx::{AppContext, HttpError, HttpTags, Server};
 
 #[derive(Clone)]
 pub struct AppState {
@@ -48,7 +48,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // automatic health-check route
     // automatic route response status code log
-    start(port, context, router).await
+    Server::builder(context, router).port(port).run().await
 }
 
 #[derive(serde::Serialize)]
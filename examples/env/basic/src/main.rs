@@ -5,7 +5,7 @@ use axum::Router;
 use serde::Deserialize;
 use derust::envx::{load_app_config, Environment};
 use derust::httpx::json::JsonResponse;
-use derust::httpx::{start, AppContext, HttpError, HttpTags};
+use derust::httpx::{AppContext, HttpError, HttpTags, Server};
 
 #[derive(Clone)]
 pub struct AppState {
@@ -51,7 +51,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // automatic health-check route
     // automatic route response status code log
-    start(port, context, router).await
+    Server::builder(context, router).port(port).run().await
 }
 
 #[derive(serde::Serialize)]
@@ -8,7 +8,7 @@ use derust::envx::Environment;
 use derust::growthbookx;
 use derust::growthbookx::{growth_book_attributes, GrowthBookConfig};
 use derust::httpx::json::JsonResponse;
-use derust::httpx::{start, AppContext, GrowthBookClientTrait, HttpError, HttpTags};
+use derust::httpx::{AppContext, GrowthBookClientTrait, HttpError, HttpTags, Server};
 
 #[derive(Clone)]
 pub struct AppState {
@@ -46,7 +46,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // automatic health-check route
     // automatic route response status code log
-    start(port, context, router).await
+    Server::builder(context, router).port(port).run().await
 }
 
 #[derive(serde::Serialize)]
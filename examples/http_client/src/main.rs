@@ -5,7 +5,7 @@ use axum::Router;
 use serde::{Deserialize, Serialize};
 use derust::envx::Environment;
 use derust::httpx::json::JsonResponse;
-use derust::httpx::{start, AppContext, HttpError, HttpTags};
+use derust::httpx::{AppContext, HttpError, HttpTags, Server};
 use derust::http_clientx::{HttpClient, Response};
 
 #[derive(Clone)]
@@ -34,7 +34,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // automatic health-check route
     // automatic route response status code log
-    start(port, context, router).await
+    Server::builder(context, router).port(port).run().await
 }
 
 #[derive(Deserialize)]
@@ -4,8 +4,8 @@ use axum::routing::get;
 use axum::Router;
 use derust::envx::Environment;
 use derust::httpx::json::JsonResponse;
-use derust::httpx::{start, AppContext, HttpError, HttpTags};
-use derust::metricx::{current_gauge, increment, increment_one, record_duration, record_money, start_stopwatch, MetricTags, PrometheusConfig};
+use derust::httpx::{AppContext, HttpError, HttpTags, Server};
+use derust::metricx::{current_gauge, increment, increment_one, record_duration, record_money, start_stopwatch, Matcher, MetricTags, PrometheusConfig, MONEY_BUCKETS};
 use regex::Regex;
 
 #[derive(Clone)]
@@ -21,20 +21,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let application_name = "sample";
 
     let prometheus_config = PrometheusConfig {
-        denied_metric_tags: vec!["customer".to_string()], // any high cardinality http tags (log tags)
-        denied_metric_tags_by_regex: vec![Regex::new(".+_id$").unwrap()], // any high cardinality http tags regex (log tags)
+        metric_buckets: vec![(Matcher::Full("order_paid".to_string()), MONEY_BUCKETS.to_vec())], // currency amounts instead of seconds
+        ..PrometheusConfig::default()
     };
 
     // easy way to get application context things, like your application state struct
     // automatic metrics route
-    let context = AppContext::new(application_name, env, prometheus_config, app_state)?;
+    let context = AppContext::new(application_name, env, prometheus_config, app_state)?
+        .with_denied_metric_tags(vec!["customer".to_string()]) // any high cardinality http tags (log tags), left out of every registry
+        .with_denied_metric_tags_by_regex(vec![Regex::new(".+_id$").unwrap()]); // any high cardinality http tags regex (log tags)
 
     let port = 3000;
     let router = Router::new().nest("/foo", Router::new().route("/", get(handler)));
 
     // automatic health-check route
     // automatic route response status code log
-    start(port, context, router).await
+    Server::builder(context, router).port(port).run().await
 }
 
 #[derive(serde::Serialize)]
@@ -53,7 +55,7 @@ async fn handler(
     increment(&context, "counter_metric_name", MetricTags::from(&tags), 10);
     increment_one(&context, "counter_metric_name", MetricTags::from(&tags));
     current_gauge(&context, "gauge_metric_name", MetricTags::from(&tags), 100.0);
-    record_money(&context, "money_metric_name", MetricTags::from(&tags), 100.0);
+    record_money(&context, "order_paid", MetricTags::from(&tags), 100.0);
     record_duration(&context, "duration_metric_name", MetricTags::from(&tags), 100.0);
 
     let stopwatch = start_stopwatch(&context,"duration_metric_name", MetricTags::from(&tags));
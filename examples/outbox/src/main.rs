@@ -8,7 +8,7 @@ use uuid::Uuid;
 use derust::databasex::{DatabaseConfig, PostgresDatabase};
 use derust::envx::{load_app_config, Environment};
 use derust::httpx::json::JsonResponse;
-use derust::httpx::{start, AppContext, HttpError, HttpTags};
+use derust::httpx::{AppContext, HttpError, HttpTags, Server};
 use derust::outboxx;
 use derust::outboxx::OutboxProcessorResources;
 
@@ -53,7 +53,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // automatic health-check route
     // automatic route response status code log
     // automatic start outbox-pattern-processor
-    start(port, context, router, outbox_resources).await
+    Server::builder(context, router)
+        .port(port)
+        .with_outbox(outbox_resources)
+        .run()
+        .await
 }
 
 #[derive(serde::Serialize)]